@@ -0,0 +1,142 @@
+//! Compile-time benchmark for the derive macro: stamps out a number of
+//! enums with many variants of each kind. Time it with
+//! `touch examples/large_enum.rs && cargo build --example large_enum --timings`.
+use variant_access_derive::*;
+use variant_access_traits::*;
+
+macro_rules! large_enum {
+    ($name:ident) => {
+        #[derive(VariantAccess, Debug, PartialEq)]
+        pub enum $name {
+            S1([u8; 1]),
+            S2([u8; 2]),
+            S3([u8; 3]),
+            S4([u8; 4]),
+            S5([u8; 5]),
+            S6([u8; 6]),
+            S7([u8; 7]),
+            S8([u8; 8]),
+            S9([u8; 9]),
+            S10([u8; 10]),
+            S11([u8; 11]),
+            S12([u8; 12]),
+            S13([u8; 13]),
+            S14([u8; 14]),
+            S15([u8; 15]),
+            S16([u8; 16]),
+            S17([u8; 17]),
+            S18([u8; 18]),
+            S19([u8; 19]),
+            S20([u8; 20]),
+            S21([u8; 21]),
+            S22([u8; 22]),
+            S23([u8; 23]),
+            S24([u8; 24]),
+            S25([u8; 25]),
+            S26([u8; 26]),
+            S27([u8; 27]),
+            S28([u8; 28]),
+            S29([u8; 29]),
+            S30([u8; 30]),
+            S31([u8; 31]),
+            S32([u8; 32]),
+            S33([u8; 33]),
+            S34([u8; 34]),
+            S35([u8; 35]),
+            S36([u8; 36]),
+            S37([u8; 37]),
+            S38([u8; 38]),
+            S39([u8; 39]),
+            S40([u8; 40]),
+            S41([u8; 41]),
+            S42([u8; 42]),
+            S43([u8; 43]),
+            S44([u8; 44]),
+            S45([u8; 45]),
+            S46([u8; 46]),
+            S47([u8; 47]),
+            S48([u8; 48]),
+            S49([u8; 49]),
+            S50([u8; 50]),
+            S51([u8; 51]),
+            S52([u8; 52]),
+            S53([u8; 53]),
+            S54([u8; 54]),
+            S55([u8; 55]),
+            S56([u8; 56]),
+            S57([u8; 57]),
+            S58([u8; 58]),
+            S59([u8; 59]),
+            S60([u8; 60]),
+            S61([u8; 61]),
+            S62([u8; 62]),
+            S63([u8; 63]),
+            S64([u8; 64]),
+            T1([u16; 1], bool),
+            T2([u16; 2], bool),
+            T3([u16; 3], bool),
+            T4([u16; 4], bool),
+            T5([u16; 5], bool),
+            T6([u16; 6], bool),
+            T7([u16; 7], bool),
+            T8([u16; 8], bool),
+            T9([u16; 9], bool),
+            T10([u16; 10], bool),
+            T11([u16; 11], bool),
+            T12([u16; 12], bool),
+            T13([u16; 13], bool),
+            T14([u16; 14], bool),
+            T15([u16; 15], bool),
+            T16([u16; 16], bool),
+            N1 { x: [u32; 1], y: String },
+            N2 { x: [u32; 2], y: String },
+            N3 { x: [u32; 3], y: String },
+            N4 { x: [u32; 4], y: String },
+            N5 { x: [u32; 5], y: String },
+            N6 { x: [u32; 6], y: String },
+            N7 { x: [u32; 7], y: String },
+            N8 { x: [u32; 8], y: String },
+            N9 { x: [u32; 9], y: String },
+            N10 { x: [u32; 10], y: String },
+            N11 { x: [u32; 11], y: String },
+            N12 { x: [u32; 12], y: String },
+            N13 { x: [u32; 13], y: String },
+            N14 { x: [u32; 14], y: String },
+            N15 { x: [u32; 15], y: String },
+            N16 { x: [u32; 16], y: String },
+            U1,
+            U2,
+            U3,
+            U4,
+        }
+    };
+}
+
+large_enum!(Large1);
+large_enum!(Large2);
+large_enum!(Large3);
+large_enum!(Large4);
+large_enum!(Large5);
+large_enum!(Large6);
+large_enum!(Large7);
+large_enum!(Large8);
+large_enum!(Large9);
+large_enum!(Large10);
+large_enum!(Large11);
+large_enum!(Large12);
+large_enum!(Large13);
+large_enum!(Large14);
+large_enum!(Large15);
+large_enum!(Large16);
+large_enum!(Large17);
+large_enum!(Large18);
+large_enum!(Large19);
+large_enum!(Large20);
+
+fn main() {
+    let mut instance = Large1::create_variant_from([0_u8; 1]);
+    instance.set_variant(([0_u16; 2], true));
+    let (_, flag): (&[u16; 2], &bool) = (&instance).get_variant().unwrap();
+    assert!(*flag);
+    assert!(instance.has_variant::<[u8; 64]>());
+}
@@ -1,6 +1,6 @@
-/// This hand written example closely resembles the code produced by the derive macro.
-/// It has been included to demonstrate how the traits work under the hood and perhaps
-/// inspire others in how they might write these traits themselves.
+//! This hand written example closely resembles the code produced by the derive macro.
+//! It has been included to demonstrate how the traits work under the hood and perhaps
+//! inspire others in how they might write these traits themselves.
 
 enum Enum {
     F1(i64),
@@ -15,6 +15,13 @@ fn is_float<T: 'static>() -> bool {
     std::any::TypeId::of::<f64>() == std::any::TypeId::of::<T>()
 }
 
+fn active_type_name(value: &Enum) -> &'static str {
+    match value {
+        Enum::F1(_) => std::any::type_name::<i64>(),
+        Enum::F2(_) => std::any::type_name::<f64>(),
+    }
+}
+
 impl variant_access_traits::ContainsVariant for Enum {
     fn has_variant<T: 'static>(&self) -> bool {
         is_integral::<T>() || is_float::<T>()
@@ -29,67 +36,86 @@ impl variant_access_traits::ContainsVariant for Enum {
             }
         } else {
             Err(variant_access_traits::VariantAccessError::invalid_type(
-                "Enum",
+                std::any::type_name::<Enum>(),
                 std::any::type_name::<T>(),
+                active_type_name(self),
             ))
         }
     }
 }
 
 impl variant_access_traits::GetVariant<i64> for Enum {
-    fn get_variant(self) -> Result<i64, variant_access_traits::VariantAccessError> {
+    fn get_variant(self) -> Result<i64, variant_access_traits::VariantAccessError<Enum>> {
         match self {
             Enum::F1(inner) => Ok(inner),
-            Enum::F2(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F2(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<i64>(),
+                active_type_name(&self),
+            )
+            .with_inner(self)),
         }
     }
+}
 
+impl variant_access_traits::GetVariantRef<i64> for Enum {
     fn get_variant_ref(&self) -> Result<&i64, variant_access_traits::VariantAccessError> {
         match &self {
             Enum::F1(inner) => Ok(inner),
-            Enum::F2(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F2(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<i64>(),
+                active_type_name(self),
+            )),
         }
     }
 
     fn get_variant_mut(&mut self) -> Result<&mut i64, variant_access_traits::VariantAccessError> {
         match self {
             Enum::F1(inner) => Ok(inner),
-            Enum::F2(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F2(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<i64>(),
+                active_type_name(self),
+            )),
         }
     }
 }
 
 impl variant_access_traits::GetVariant<f64> for Enum {
-    fn get_variant(self) -> Result<f64, variant_access_traits::VariantAccessError> {
+    fn get_variant(self) -> Result<f64, variant_access_traits::VariantAccessError<Enum>> {
         match self {
             Enum::F2(inner) => Ok(inner),
-            Enum::F1(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F1(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<f64>(),
+                active_type_name(&self),
+            )
+            .with_inner(self)),
         }
     }
+}
 
+impl variant_access_traits::GetVariantRef<f64> for Enum {
     fn get_variant_ref(&self) -> Result<&f64, variant_access_traits::VariantAccessError> {
         match self {
             Enum::F2(inner) => Ok(inner),
-            Enum::F1(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F1(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<f64>(),
+                active_type_name(self),
+            )),
         }
     }
 
     fn get_variant_mut(&mut self) -> Result<&mut f64, variant_access_traits::VariantAccessError> {
         match self {
             Enum::F2(inner) => Ok(inner),
-            Enum::F1(_) => {
-                Err(variant_access_traits::VariantAccessError::wrong_active_field("Enum", "i64"))
-            }
+            Enum::F1(_) => Err(variant_access_traits::VariantAccessError::wrong_active_field(
+                std::any::type_name::<Enum>(),
+                std::any::type_name::<f64>(),
+                active_type_name(self),
+            )),
         }
     }
 }
@@ -118,4 +144,16 @@ impl variant_access_traits::CreateVariantFrom<f64> for Enum {
     }
 }
 
-fn main() {}
+fn main() {
+    use variant_access_traits::*;
+
+    let mut instance = Enum::create_variant_from(42_i64);
+    assert!(instance.has_variant::<f64>());
+    assert!(instance.contains_variant::<i64>().unwrap());
+    instance.set_variant(1.0_f64);
+    *instance.get_variant_mut().unwrap() = 2.0_f64;
+    let inner: &f64 = instance.get_variant_ref().unwrap();
+    assert_eq!(*inner, 2.0);
+    let inner: f64 = instance.get_variant().unwrap();
+    assert_eq!(inner, 2.0);
+}
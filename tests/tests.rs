@@ -150,6 +150,1368 @@ mod test_basic {
     }
 }
 
+#[cfg(test)]
+mod test_multiple_fields {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(i64, String),
+        F2(bool),
+    }
+
+    #[test]
+    fn test_contains_variant() {
+        let test = Test::F1(42, String::from("42"));
+        assert!(test.has_variant::<(i64, String)>());
+        assert!(!test.has_variant::<i64>());
+        assert!(test.contains_variant::<(i64, String)>().expect("Test failed"));
+        assert!(!test.contains_variant::<bool>().expect("Test failed"));
+    }
+
+    #[test]
+    fn test_get_variant() {
+        let test = Test::F1(42, String::from("42"));
+        let inner: (i64, String) = test.get_variant().expect("Test failed");
+        assert_eq!(inner, (42, String::from("42")));
+    }
+
+    #[test]
+    fn test_get_variant_borrowed() {
+        let test = Test::F1(42, String::from("42"));
+        let (num, string): (&i64, &String) = (&test).get_variant().expect("Test failed");
+        assert_eq!(num, &42);
+        assert_eq!(string, "42");
+        let test = Test::F2(true);
+        let _ = <&Test as GetVariant<(&i64, &String), _>>::get_variant(&test)
+            .expect_err("Test failed");
+    }
+
+    #[test]
+    fn test_get_variant_borrowed_mut() {
+        let mut test = Test::F1(42, String::from("42"));
+        let (num, string): (&mut i64, &mut String) =
+            (&mut test).get_variant().expect("Test failed");
+        *num = 1;
+        string.push('!');
+        assert_eq!(test, Test::F1(1, String::from("42!")));
+    }
+
+    #[test]
+    fn test_set_variant() {
+        let mut test = Test::F2(false);
+        test.set_variant((1_i64, String::from("1")));
+        assert_eq!(test, Test::F1(1, String::from("1")));
+    }
+
+    #[test]
+    fn test_create_variant_from() {
+        let test = Test::create_variant_from((1_i64, String::from("1")));
+        assert_eq!(test, Test::F1(1, String::from("1")));
+        let test: Test = create_variant_from((2_i64, String::from("2")));
+        assert_eq!(test, Test::F1(2, String::from("2")));
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Tree {
+        Node(Box<Self>, i64),
+        Val(i64),
+    }
+
+    #[test]
+    /// `Self` in the fields refers to the enum in the impls for references to it
+    fn test_self_in_fields() {
+        let mut test = Tree::create_variant_from((Box::new(Tree::Val(1)), 2_i64));
+        let (child, value): (_, &i64) = (&test).get_variant().expect("Test failed");
+        assert_eq!((child.as_ref(), *value), (&Tree::Val(1), 2));
+        let (child, _): (_, &mut i64) = (&mut test).get_variant().expect("Test failed");
+        **child = Tree::Val(3);
+        assert_eq!(test, Tree::Node(Box::new(Tree::Val(3)), 2));
+    }
+}
+
+#[cfg(test)]
+mod test_named_fields {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Complex {
+        field: f64,
+    }
+
+    pub mod namespace {
+        use super::*;
+
+        #[derive(VariantAccess, PartialEq, Debug)]
+        pub enum Test<T: 'static> {
+            F1(i64),
+            F2 { x: bool, y: T },
+            F3 { complex: super::Complex, next: Option<Box<Self>> },
+        }
+    }
+
+    use namespace::{variant_access_Test, Test};
+
+    #[test]
+    fn test_contains_variant() {
+        let test = Test::<f64>::F2 { x: true, y: 2.0 };
+        assert!(test.has_variant::<variant_access_Test::F2<f64>>());
+        assert!(test
+            .contains_variant::<variant_access_Test::F2<f64>>()
+            .expect("Test failed"));
+        assert!(!test
+            .contains_variant::<variant_access_Test::F3<f64>>()
+            .expect("Test failed"));
+    }
+
+    #[test]
+    fn test_get_variant() {
+        let test = Test::<f64>::F2 { x: true, y: 2.0 };
+        let variant_access_Test::F2 { x, y } = test.get_variant().expect("Test failed");
+        assert!(x);
+        assert_eq!(y, 2.0);
+        let test = Test::<f64>::F1(1);
+        let _ = <Test<f64> as GetVariant<variant_access_Test::F2<f64>, _>>::get_variant(test)
+            .expect_err("Test failed");
+    }
+
+    #[test]
+    fn test_get_variant_borrowed() {
+        let test = Test::<f64>::F3 {
+            complex: Complex { field: 1.0 },
+            next: None,
+        };
+        let variant_access_Test::F3Ref { complex, next } =
+            (&test).get_variant().expect("Test failed");
+        assert_eq!(complex, &Complex { field: 1.0 });
+        assert_eq!(next, &None);
+    }
+
+    #[test]
+    fn test_get_variant_borrowed_mut() {
+        let mut test = Test::<f64>::F2 { x: true, y: 2.0 };
+        let view: variant_access_Test::F2Mut<f64> = (&mut test).get_variant().expect("Test failed");
+        *view.x = false;
+        *view.y = 3.0;
+        assert_eq!(test, Test::F2 { x: false, y: 3.0 });
+    }
+
+    #[test]
+    fn test_set_variant() {
+        let mut test = Test::<f64>::F1(1);
+        test.set_variant(variant_access_Test::F2 { x: true, y: 2.0 });
+        assert_eq!(test, Test::F2 { x: true, y: 2.0 });
+    }
+
+    #[test]
+    fn test_create_variant_from() {
+        let test = Test::<f64>::create_variant_from(variant_access_Test::F3 {
+            complex: Complex { field: 1.0 },
+            next: Some(Box::new(Test::F1(1))),
+        });
+        assert_eq!(
+            test,
+            Test::F3 {
+                complex: Complex { field: 1.0 },
+                next: Some(Box::new(Test::F1(1)))
+            }
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Circle {
+        radius: f64,
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Shape {
+        Circle(Circle),
+        Pair { a: Circle, b: Circle },
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Json {
+        String(String),
+        Object { keys: Vec<String> },
+    }
+
+    #[test]
+    /// Variants named like the types of fields of other variants do not
+    /// shadow these types
+    fn test_variant_named_like_field_type() {
+        let mut shape = Shape::create_variant_from(Circle { radius: 1.0 });
+        shape.set_variant(variant_access_Shape::Pair {
+            a: Circle { radius: 2.0 },
+            b: Circle { radius: 3.0 },
+        });
+        let variant_access_Shape::PairRef { a, b } = (&shape).get_variant().expect("Test failed");
+        assert_eq!((a.radius, b.radius), (2.0, 3.0));
+    }
+
+    #[test]
+    /// As above, for a variant named like a type from the prelude
+    fn test_variant_named_like_prelude_type() {
+        let json = Json::create_variant_from(variant_access_Json::Object {
+            keys: vec![String::from("key")],
+        });
+        let variant_access_Json::Object { keys } = json.get_variant().expect("Test failed");
+        assert_eq!(keys, vec![String::from("key")]);
+        let json = Json::create_variant_from(String::from("value"));
+        assert_eq!(json, Json::String(String::from("value")));
+    }
+}
+
+#[cfg(test)]
+mod test_unit_variants {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        Empty,
+        Nothing(),
+        F1(i64),
+    }
+
+    #[test]
+    fn test_contains_variant() {
+        let test = Test::Empty;
+        assert!(test.has_variant::<variant_access_Test::Empty>());
+        assert!(test.has_variant::<variant_access_Test::Nothing>());
+        assert!(test
+            .contains_variant::<variant_access_Test::Empty>()
+            .expect("Test failed"));
+        assert!(!test
+            .contains_variant::<variant_access_Test::Nothing>()
+            .expect("Test failed"));
+        assert!(!test.contains_variant::<i64>().expect("Test failed"));
+    }
+
+    #[test]
+    fn test_get_variant() {
+        let test = Test::Empty;
+        let _: &variant_access_Test::Empty = test.get_variant_ref().expect("Test failed");
+        let inner: variant_access_Test::Empty = test.get_variant().expect("Test failed");
+        assert_eq!(inner, variant_access_Test::Empty);
+        let mut test = Test::F1(1);
+        let result: Result<&mut variant_access_Test::Empty, _> = test.get_variant_mut();
+        let _ = result.expect_err("Test failed");
+    }
+
+    #[test]
+    fn test_set_variant() {
+        let mut test = Test::F1(1);
+        test.set_variant(variant_access_Test::Empty);
+        assert_eq!(test, Test::Empty);
+        test.set_variant(variant_access_Test::Nothing);
+        assert_eq!(test, Test::Nothing());
+    }
+
+    #[test]
+    fn test_create_variant_from() {
+        let test = Test::create_variant_from(variant_access_Test::Empty);
+        assert_eq!(test, Test::Empty);
+        let test: Test = create_variant_from(variant_access_Test::Nothing);
+        assert_eq!(test, Test::Nothing());
+    }
+}
+
+#[cfg(test)]
+mod test_lifetimes {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Token<'a> {
+        Ident(&'a str),
+        Num(i64),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Borrowed<'a, 'b, T> {
+        F1(&'a T),
+        F2(&'b mut Vec<T>, usize),
+        F3 { name: &'a str },
+    }
+
+    #[test]
+    fn test_is_variant() {
+        let source = String::from("x + 1");
+        let token = Token::Ident(&source[..1]);
+        assert!(IsVariant::<&str, _>::is_variant(&token));
+        assert!(!IsVariant::<i64, _>::is_variant(&token));
+    }
+
+    #[test]
+    fn test_get_variant() {
+        let source = String::from("x + 1");
+        let mut token = Token::Ident(&source[..1]);
+        let inner: &&str = token.get_variant_ref().expect("Test failed");
+        assert_eq!(*inner, "x");
+        let result: Result<&mut i64, _> = token.get_variant_mut();
+        let _ = result.expect_err("Test failed");
+        let inner: &str = token.get_variant().expect("Test failed");
+        assert_eq!(inner, "x");
+
+        let mut values = vec![1, 2];
+        let mut test = Borrowed::F2(&mut values, 0);
+        let (inner, _): (&mut &mut Vec<i32>, &mut usize) =
+            (&mut test).get_variant().expect("Test failed");
+        inner.push(3);
+        let (inner, index): (&mut Vec<i32>, usize) = test.get_variant().expect("Test failed");
+        assert_eq!(inner[index], 1);
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_set_variant() {
+        let source = String::from("x + 1");
+        let mut token = Token::Num(1);
+        token.set_variant(&source[..1]);
+        assert_eq!(token, Token::Ident("x"));
+
+        let mut test = Borrowed::F1(&1);
+        test.set_variant(variant_access_Borrowed::F3 { name: &source[..1] });
+        assert_eq!(test, Borrowed::F3 { name: "x" });
+    }
+
+    #[test]
+    fn test_create_variant_from() {
+        let source = String::from("x + 1");
+        let token = Token::create_variant_from(&source[..1]);
+        assert_eq!(token, Token::Ident("x"));
+        let token: Token = create_variant_from(1_i64);
+        assert_eq!(token, Token::Num(1));
+    }
+}
+
+#[cfg(test)]
+mod test_active_variant {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test<T: 'static> {
+        F1(T),
+        #[variant_access(skip)]
+        F2(i64, bool),
+        F3 { x: T },
+        F4,
+    }
+
+    #[test]
+    fn test_active_variant() {
+        let test = Test::F1(1_i64);
+        assert_eq!(test.variant_index(), 0);
+        assert_eq!(test.variant_name(), "F1");
+        assert_eq!(test.active_type_name(), "i64");
+        assert_eq!(test.active_type_id(), std::any::TypeId::of::<i64>());
+
+        let test = Test::<i64>::F2(1, true);
+        assert_eq!(test.variant_index(), 1);
+        assert_eq!(test.variant_name(), "F2");
+        assert_eq!(test.active_type_name(), "(i64, bool)");
+        assert_eq!(test.active_type_id(), std::any::TypeId::of::<(i64, bool)>());
+
+        let test = Test::F3 { x: 1_i64 };
+        assert_eq!(test.variant_index(), 2);
+        assert_eq!(test.variant_name(), "F3");
+        assert_eq!(
+            test.active_type_id(),
+            std::any::TypeId::of::<variant_access_Test::F3<i64>>()
+        );
+
+        let test = Test::<i64>::F4;
+        assert_eq!(test.variant_index(), 3);
+        assert_eq!(test.variant_name(), "F4");
+        assert_eq!(test.active_type_id(), std::any::TypeId::of::<variant_access_Test::F4>());
+    }
+
+    #[test]
+    fn test_variant_types() {
+        assert_eq!(Test::<bool>::VARIANT_COUNT, 3);
+        assert_eq!(Test::<bool>::variant_names(), ["F1", "F3", "F4"]);
+        assert_eq!(
+            Test::<bool>::type_ids(),
+            vec![
+                std::any::TypeId::of::<bool>(),
+                std::any::TypeId::of::<variant_access_Test::F3<bool>>(),
+                std::any::TypeId::of::<variant_access_Test::F4>(),
+            ]
+        );
+        assert_eq!(Test::<bool>::type_names()[0], "bool");
+        assert_eq!(Test::<bool>::index_of::<bool>(), Some(0));
+        assert_eq!(Test::<bool>::index_of::<variant_access_Test::F4>(), Some(2));
+        assert_eq!(Test::<bool>::index_of::<(i64, bool)>(), None);
+    }
+}
+
+#[cfg(test)]
+mod test_variant_at {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test<T: 'static> {
+        F1(i32),
+        F2(i64),
+        F3(T, bool),
+        F4 { x: T },
+        F5,
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Token<'a> {
+        Ident(&'a str),
+        Num(i64),
+    }
+
+    #[test]
+    fn test_get_at() {
+        let mut test = Test::<String>::F2(1);
+        let inner: &i64 = test.get_at::<1>().expect("Test failed");
+        assert_eq!(*inner, 1);
+        *test.get_at_mut::<1>().expect("Test failed") += 1;
+        assert_eq!(test, Test::F2(2));
+        let _ = test.get_at::<0>().expect_err("Test failed");
+
+        let test = Test::<String>::F5;
+        let inner: &variant_access_Test::F5 = VariantAtRef::<4>::get_at(&test).expect("Test failed");
+        assert_eq!(*inner, variant_access_Test::F5);
+
+        let source = String::from("x + 1");
+        let token = Token::Ident(&source[..1]);
+        assert_eq!(*token.get_at::<0>().expect("Test failed"), "x");
+    }
+
+    #[test]
+    fn test_set_at() {
+        let mut test = Test::<String>::F5;
+        test.set_at::<0>(1);
+        assert_eq!(test, Test::F1(1));
+        test.set_at::<1>(1);
+        assert_eq!(test, Test::F2(1));
+        test.set_at::<2>((String::from("x"), true));
+        assert_eq!(test, Test::F3(String::from("x"), true));
+        test.set_at::<3>(variant_access_Test::F4 { x: String::from("y") });
+        assert_eq!(test, Test::F4 { x: String::from("y") });
+    }
+
+    #[test]
+    fn test_create_at() {
+        assert_eq!(Test::<bool>::create_at::<0>(1), Test::F1(1));
+        assert_eq!(Test::<bool>::create_at::<1>(1), Test::F2(1));
+        assert_eq!(
+            <Test<bool> as VariantAt<4>>::create_at(variant_access_Test::F5),
+            Test::F5
+        );
+        assert_eq!(Token::create_at::<1>(1), Token::Num(1));
+    }
+}
+
+#[cfg(test)]
+mod test_visit {
+    use super::*;
+    use std::fmt::Display;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test<T: 'static> {
+        F1(i64),
+        F2(String),
+        F3(T),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Shapes {
+        Point(f64, f64),
+        Circle { radius: f64 },
+        Empty,
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Token<'a> {
+        Ident(&'a str),
+        Num(i64),
+    }
+
+    struct Printer;
+
+    impl Visitor for Printer {
+        type Output = String;
+    }
+
+    impl<T: Display> VisitType<T> for Printer {
+        fn visit_type(self, value: T) -> String {
+            value.to_string()
+        }
+    }
+
+    struct Reset;
+
+    impl Visitor for Reset {
+        type Output = ();
+    }
+
+    impl<T: Default> VisitType<&mut T> for Reset {
+        fn visit_type(self, value: &mut T) {
+            *value = T::default();
+        }
+    }
+
+    struct Area;
+
+    impl Visitor for Area {
+        type Output = f64;
+    }
+
+    impl VisitType<(&f64, &f64)> for Area {
+        fn visit_type(self, _: (&f64, &f64)) -> f64 {
+            0.0
+        }
+    }
+
+    impl<'a> VisitType<variant_access_Shapes::CircleRef<'a>> for Area {
+        fn visit_type(self, value: variant_access_Shapes::CircleRef<'a>) -> f64 {
+            3.0 * value.radius * value.radius
+        }
+    }
+
+    impl VisitType<&variant_access_Shapes::Empty> for Area {
+        fn visit_type(self, _: &variant_access_Shapes::Empty) -> f64 {
+            0.0
+        }
+    }
+
+    struct Scale(f64);
+
+    impl Visitor for Scale {
+        type Output = ();
+    }
+
+    impl VisitType<(&mut f64, &mut f64)> for Scale {
+        fn visit_type(self, (x, y): (&mut f64, &mut f64)) {
+            *x *= self.0;
+            *y *= self.0;
+        }
+    }
+
+    impl<'a> VisitType<variant_access_Shapes::CircleMut<'a>> for Scale {
+        fn visit_type(self, value: variant_access_Shapes::CircleMut<'a>) {
+            *value.radius *= self.0;
+        }
+    }
+
+    impl VisitType<&mut variant_access_Shapes::Empty> for Scale {
+        fn visit_type(self, _: &mut variant_access_Shapes::Empty) {}
+    }
+
+    #[test]
+    fn test_visit() {
+        assert_eq!(Test::<bool>::F1(42).visit(Printer), "42");
+        assert_eq!(Test::<bool>::F2(String::from("x")).visit(Printer), "x");
+        assert_eq!(Test::F3(true).visit(Printer), "true");
+
+        assert_eq!(Shapes::Circle { radius: 1.0 }.visit(Area), 3.0);
+        assert_eq!(Shapes::Point(1.0, 2.0).visit(Area), 0.0);
+        assert_eq!(Shapes::Empty.visit(Area), 0.0);
+
+        let source = String::from("x + 1");
+        assert_eq!(Token::Ident(&source[..1]).visit(Printer), "x");
+    }
+
+    #[test]
+    fn test_visit_mut() {
+        let mut test = Test::<bool>::F2(String::from("x"));
+        test.visit_mut(Reset);
+        assert_eq!(test, Test::F2(String::new()));
+
+        let mut test = Shapes::Point(1.0, 2.0);
+        test.visit_mut(Scale(2.0));
+        assert_eq!(test, Shapes::Point(2.0, 4.0));
+        let mut test = Shapes::Circle { radius: 1.0 };
+        test.visit_mut(Scale(2.0));
+        assert_eq!(test, Shapes::Circle { radius: 2.0 });
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Operand {
+        Int(i64),
+        Float(f64),
+    }
+
+    struct Add;
+
+    impl Visitor for Add {
+        type Output = f64;
+    }
+
+    impl VisitPair<&i64, &i64> for Add {
+        fn visit_pair(self, first: &i64, second: &i64) -> f64 {
+            (first + second) as f64
+        }
+    }
+
+    impl VisitPair<&i64, &f64> for Add {
+        fn visit_pair(self, first: &i64, second: &f64) -> f64 {
+            *first as f64 + second
+        }
+    }
+
+    impl VisitPair<&f64, &i64> for Add {
+        fn visit_pair(self, first: &f64, second: &i64) -> f64 {
+            first + *second as f64
+        }
+    }
+
+    impl VisitPair<&f64, &f64> for Add {
+        fn visit_pair(self, first: &f64, second: &f64) -> f64 {
+            first + second
+        }
+    }
+
+    struct Join;
+
+    impl Visitor for Join {
+        type Output = String;
+    }
+
+    impl<A: Display, B: Display> VisitPair<A, B> for Join {
+        fn visit_pair(self, first: A, second: B) -> String {
+            format!("{} {}", first, second)
+        }
+    }
+
+    impl<A: Display, B: Display, C: Display> VisitTuple<(A, B, C)> for Join {
+        fn visit_tuple(self, (a, b, c): (A, B, C)) -> String {
+            format!("{} {} {}", a, b, c)
+        }
+    }
+
+    #[test]
+    fn test_visit2() {
+        assert_eq!(visit2(&Operand::Int(1), &Operand::Int(2), Add), 3.0);
+        assert_eq!(visit2(&Operand::Int(1), &Operand::Float(0.5), Add), 1.5);
+        assert_eq!(visit2(&Operand::Float(0.5), &Operand::Int(1), Add), 1.5);
+        assert_eq!(visit2(&Operand::Float(0.5), &Operand::Float(0.5), Add), 1.0);
+
+        let source = String::from("x + 1");
+        let token = Token::Ident(&source[..1]);
+        assert_eq!(visit2(&token, &Test::F3(true), Join), "x true");
+    }
+
+    #[test]
+    fn test_visit_n() {
+        assert_eq!(visit!(Join; &Operand::Int(1), &Test::<bool>::F1(2)), "1 2");
+        assert_eq!(
+            visit!(Join; &Operand::Int(1), &Test::F3('a'), &Operand::Float(0.5)),
+            "1 a 0.5"
+        );
+    }
+
+    #[test]
+    fn test_into_visit() {
+        assert_eq!(Test::<bool>::F2(String::from("x")).into_visit(Printer), "x");
+        assert_eq!(Test::F3(1_u8).into_visit(Printer), "1");
+    }
+
+    #[derive(VariantAccess)]
+    enum Never {}
+
+    fn visit_never(never: &Never) -> String {
+        never.visit(Printer)
+    }
+
+    #[test]
+    fn test_visit_empty_enum() {
+        let _: fn(&Never) -> String = visit_never;
+        let _: fn(Never) -> String = |never| never.into_visit(Printer);
+    }
+
+    #[derive(PartialEq, Debug)]
+    pub struct V(i64);
+
+    impl Display for V {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "V{}", self.0)
+        }
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Named {
+        F1(V),
+        F2(bool),
+    }
+
+    #[test]
+    /// A field type named like the visitor parameter is not shadowed by it
+    fn test_visit_field_named_v() {
+        assert_eq!(Named::F1(V(1)).visit(Printer), "V1");
+        assert_eq!(Named::F2(true).into_visit(Printer), "true");
+    }
+}
+
+#[cfg(test)]
+mod test_errors {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test<T: 'static> {
+        F1(i64),
+        F2(T),
+        F3(i64, String),
+    }
+
+    #[test]
+    fn test_wrong_active_field() {
+        let test: Test<bool> = Test::F1(1);
+        let error = test.get::<bool, _>().expect_err("Test failed");
+        assert_eq!(error.kind(), ErrorKind::WrongActiveField);
+        assert_eq!(error.enum_name(), std::any::type_name::<Test<bool>>());
+        assert_eq!(error.requested_type(), "bool");
+        assert_eq!(error.active_type(), "i64");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "VariantAccessError :: Active field of enum <{}> is of type <i64>, not of requested type <bool>",
+                std::any::type_name::<Test<bool>>()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_type() {
+        let test: Test<bool> = Test::F2(true);
+        let error = test.contains_variant::<u8>().expect_err("Test failed");
+        assert_eq!(error.kind(), ErrorKind::InvalidType);
+        assert_eq!(error.requested_type(), "u8");
+        assert_eq!(error.active_type(), "bool");
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let test: Test<bool> = Test::F3(1, String::from("one"));
+        let error = GetVariant::<bool, _>::get_variant(test).expect_err("Test failed");
+        assert_eq!(error.active_type(), std::any::type_name::<(i64, String)>());
+        assert_eq!(error.into_inner(), Test::F3(1, String::from("one")));
+
+        let test: Test<bool> = Test::F1(1);
+        let error = GetVariant::<(&i64, &String), _>::get_variant(&test).expect_err("Test failed");
+        assert_eq!(error.into_inner(), &Test::F1(1));
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum List {
+        Cons(Box<Self>),
+        Val(i64),
+        #[variant_access(skip)]
+        Pair(Box<Self>, i64),
+    }
+
+    #[test]
+    /// `Self` in the fields refers to the enum when naming the active type
+    fn test_self_in_fields() {
+        let test = List::Cons(Box::new(List::Val(1)));
+        let error = test.get::<i64>().expect_err("Test failed");
+        assert_eq!(error.active_type(), std::any::type_name::<Box<List>>());
+
+        let test = List::Pair(Box::new(List::Val(1)), 2);
+        let error = test.get::<i64>().expect_err("Test failed");
+        assert_eq!(error.active_type(), std::any::type_name::<(Box<List>, i64)>());
+    }
+}
+
+#[cfg(test)]
+mod test_get_methods {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(i64),
+        F2(String),
+        Empty,
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Generic<T: 'static> {
+        F1(i64),
+        F2(T),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Token<'a> {
+        Ident(&'a str),
+        Num(i64),
+    }
+
+    #[test]
+    fn test_get() {
+        let mut test = Test::F1(1);
+        assert_eq!(*test.get::<i64>().expect("Test failed") + 1, 2);
+        assert!(test.get::<String>().is_err());
+        *test.get_mut::<i64>().expect("Test failed") = 3;
+        assert_eq!(test, Test::F1(3));
+        assert!(test.get_mut::<variant_access_Test::Empty>().is_err());
+    }
+
+    #[test]
+    fn test_try_get() {
+        let test = Test::F2(String::from("two"));
+        assert_eq!(test.try_get::<String>().map(String::len), Some(3));
+        assert_eq!(test.try_get::<i64>(), None);
+    }
+
+    #[test]
+    fn test_get_copied_and_cloned() {
+        let test = Test::F1(1);
+        assert_eq!(test.get_copied::<i64>().expect("Test failed"), 1);
+        assert!(test.get_cloned::<String>().is_err());
+        let test = Test::F2(String::from("two"));
+        assert_eq!(test.get_cloned::<String>().expect("Test failed"), "two");
+    }
+
+    #[test]
+    fn test_get_or() {
+        let test = Test::F1(1);
+        assert_eq!(test.get_or::<i64>(0), 1);
+        assert_eq!(test.get_or::<String>(String::from("none")), "none");
+    }
+
+    #[test]
+    fn test_get_with_lifetimes() {
+        let token = Token::Ident("x");
+        assert_eq!(token.get_copied::<&str>().expect("Test failed"), "x");
+        assert_eq!(token.try_get::<i64>(), None);
+    }
+
+    #[test]
+    fn test_get_generic() {
+        let mut test: Generic<bool> = Generic::F2(true);
+        assert_eq!(test.try_get::<bool, _>(), Some(&true));
+        assert_eq!(test.get_or::<i64, _>(0), 0);
+        *test.get_mut::<bool, _>().expect("Test failed") = false;
+        assert_eq!(test, Generic::F2(false));
+    }
+}
+
+#[cfg(test)]
+mod test_replace_variant {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(String),
+        F2(bool),
+        F3(i64, Vec<i64>),
+    }
+
+    impl Default for Test {
+        fn default() -> Self {
+            Test::F2(false)
+        }
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Generic<T: 'static> {
+        F1(i64),
+        F2(T),
+    }
+
+    #[test]
+    fn test_replace_variant() {
+        let mut test = Test::F1(String::from("one"));
+        let previous = test.replace_variant(true);
+        assert_eq!(previous, Test::F1(String::from("one")));
+        assert_eq!(test, Test::F2(true));
+    }
+
+    #[test]
+    fn test_take_variant() {
+        let mut test = Test::F1(String::from("one"));
+        assert_eq!(test.take_variant::<String>().expect("Test failed"), "one");
+        assert_eq!(test, Test::F2(false));
+        assert!(test.take_variant::<String>().is_err());
+        assert_eq!(test, Test::F2(false));
+    }
+
+    #[test]
+    fn test_take_variant_or() {
+        let mut test = Test::F3(1, vec![2]);
+        let (num, nums) = test
+            .take_variant_or::<(i64, Vec<i64>)>(Test::F1(String::new()))
+            .expect("Test failed");
+        assert_eq!((num, nums), (1, vec![2]));
+        assert_eq!(test, Test::F1(String::new()));
+        assert!(test.take_variant_or::<bool>(Test::F2(true)).is_err());
+        assert_eq!(test, Test::F1(String::new()));
+    }
+
+    #[test]
+    fn test_emplace_with() {
+        let mut test = Test::F2(true);
+        test.emplace_with::<String>(String::new).push('x');
+        assert_eq!(test, Test::F1(String::from("x")));
+        *test.emplace_with(|| true) = false;
+        assert_eq!(test, Test::F2(false));
+    }
+
+    #[test]
+    fn test_generic() {
+        let mut test: Generic<bool> = Generic::F1(1);
+        *test.emplace_with::<bool, _>(|| false) = true;
+        assert_eq!(test, Generic::F2(true));
+        assert!(test.take_variant_or::<bool, _>(Generic::F1(2)).expect("Test failed"));
+        assert_eq!(test.replace_variant(3_i64), Generic::F1(2));
+    }
+}
+
+#[cfg(test)]
+mod test_match_variant {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test<T: 'static> {
+        F1(i64),
+        F2(String),
+        F3(T),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Shapes {
+        Point(f64, f64),
+        Circle { radius: f64 },
+        Empty,
+    }
+
+    fn describe(value: Test<bool>) -> String {
+        match_variant!(value {
+            x: i64 => format!("int {}", x),
+            s: String => s,
+            b: bool => if b { "yes".to_string() } else { "no".to_string() }
+        })
+    }
+
+    #[test]
+    fn test_match_variant() {
+        assert_eq!(describe(Test::F1(1)), "int 1");
+        assert_eq!(describe(Test::F2(String::from("two"))), "two");
+        assert_eq!(describe(Test::F3(false)), "no");
+    }
+
+    #[test]
+    fn test_match_variant_any_order() {
+        let value: Test<bool> = Test::F3(true);
+        let result = match_variant!(value {
+            b: bool => {
+                assert!(b);
+                b as i64
+            }
+            mut s: String => {
+                s.push('!');
+                s.len() as i64
+            }
+            x: i64 => x,
+        });
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn test_match_variant_default() {
+        let value: Test<bool> = Test::F2(String::from("two"));
+        let result = match_variant!(value {
+            x: i64 => x,
+            _ => -1,
+        });
+        assert_eq!(result, -1);
+    }
+
+    #[test]
+    fn test_match_variant_multiple_fields() {
+        let shape = Shapes::Circle { radius: 2.0 };
+        let area = match_variant!(shape {
+            (x, y): (f64, f64) => x * y,
+            circle: variant_access_Shapes::Circle => 3.0 * circle.radius * circle.radius,
+            _: variant_access_Shapes::Empty => 0.0,
+        });
+        assert_eq!(area, 12.0);
+    }
+}
+
+#[cfg(test)]
+mod test_conversions {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::convert::TryInto;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    #[variant_access(from, try_into)]
+    enum Test {
+        F1(i64),
+        F2(String),
+        F3(i64, bool),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    #[variant_access(from, try_into)]
+    enum Generic<T: 'static> {
+        F1(i64),
+        F2(T),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    #[variant_access(from)]
+    enum Token<'a> {
+        Ident(&'a str),
+        Num(i64),
+    }
+
+    fn parse_num(test: Test) -> Result<i64, VariantAccessError<Test>> {
+        let num: i64 = test.try_into()?;
+        Ok(num + 1)
+    }
+
+    #[test]
+    fn test_from() {
+        let test: Test = 1_i64.into();
+        assert_eq!(test, Test::F1(1));
+        assert_eq!(Test::from(String::from("two")), Test::F2(String::from("two")));
+        assert_eq!(Test::from((3, true)), Test::F3(3, true));
+        assert_eq!(Token::from("x"), Token::Ident("x"));
+    }
+
+    #[test]
+    fn test_try_into() {
+        assert_eq!(parse_num(Test::F1(1)).expect("Test failed"), 2);
+        let error = parse_num(Test::F3(1, true)).expect_err("Test failed");
+        assert_eq!(error.into_inner(), Test::F3(1, true));
+        assert_eq!(<(i64, bool)>::try_from(Test::F3(1, true)).expect("Test failed"), (1, true));
+    }
+
+    #[test]
+    fn test_generic() {
+        let test: Generic<bool> = 1_i64.into();
+        assert_eq!(test, Generic::F1(1));
+        assert_eq!(i64::try_from(test).expect("Test failed"), 1);
+    }
+
+    #[test]
+    fn test_into_variant() {
+        assert_eq!(1_i64.into_variant::<Test>(), Test::F1(1));
+        assert_eq!((1_i64, false).into_variant::<Test>(), Test::F3(1, false));
+        assert_eq!(2_i64.into_variant::<Token>(), Token::Num(2));
+    }
+}
+
+#[cfg(test)]
+mod test_has_variant {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(i64),
+        F2(String),
+        F3(i64, bool),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Generic<T: 'static> {
+        F1(i64),
+        F2(T),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    #[variant_access(derive(IsVariant))]
+    enum CheckOnly {
+        F1(i64),
+        F2(bool),
+    }
+
+    fn count<E: HasVariant<i64>>(values: &[E]) -> usize {
+        values.iter().filter(|value| value.is_variant()).count()
+    }
+
+    fn reset<E: VariantAccess<i64>>(value: &mut E) {
+        value.set_variant(0);
+    }
+
+    fn into_num<E: VariantAccess<i64>>(value: E) -> Option<i64> {
+        value.get_variant().ok()
+    }
+
+    #[test]
+    fn test_contains() {
+        let test = Test::F3(1, true);
+        assert!(test.contains::<(i64, bool)>());
+        assert!(!test.contains::<i64>());
+        assert!(Generic::<bool>::F2(true).contains::<bool, _>());
+        assert!(!Generic::<bool>::F2(true).contains::<i64, _>());
+        assert!(CheckOnly::F2(true).contains::<bool>());
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(count(&[Test::F1(1), Test::F2(String::from("two")), Test::F1(3)]), 2);
+        assert_eq!(count(&[CheckOnly::F1(1), CheckOnly::F2(false)]), 1);
+        let mut test = Test::F2(String::from("two"));
+        reset(&mut test);
+        assert_eq!(test, Test::F1(0));
+        assert_eq!(into_num(test), Some(0));
+        assert_eq!(into_num(Test::F3(1, true)), None);
+    }
+}
+
+#[cfg(test)]
+mod test_type_list {
+    use super::*;
+    use std::any::type_name;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(i64),
+        F2(String),
+        F3(i64, bool),
+        Empty,
+    }
+
+    #[derive(Default)]
+    struct Names(Vec<&'static str>);
+
+    impl TypeVisitor for Names {
+        fn visit<T>(&mut self) {
+            self.0.push(type_name::<T>());
+        }
+    }
+
+    #[test]
+    fn test_for_each_type() {
+        let mut names = Names::default();
+        for_each_type::<Test, _>(&mut names);
+        assert_eq!(
+            names.0,
+            vec![
+                "i64",
+                type_name::<String>(),
+                "(i64, bool)",
+                type_name::<variant_access_Test::Empty>()
+            ]
+        );
+        assert_eq!(<<Test as Alternatives>::List as TypeList>::LEN, 4);
+    }
+
+    #[test]
+    fn test_contains_any() {
+        let test = Test::F3(1, true);
+        assert!(test.contains_any::<(i64, (i64, bool))>());
+        assert!(!test.contains_any::<(i64, String)>());
+        assert!(Test::Empty.contains_any::<(variant_access_Test::Empty,)>());
+        assert!(!Test::Empty.contains_any::<()>());
+    }
+}
+
+#[cfg(test)]
+mod test_any {
+    use super::*;
+    use std::any::{Any, TypeId};
+
+    pub struct Meters;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Test {
+        F1(i64),
+        F2(String),
+        F3(i64, bool),
+        F4 { x: bool },
+        #[variant_access(tag = Meters)]
+        F5(f64),
+        Empty,
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Generic<T: 'static> {
+        F1(i64),
+        F2(T),
+    }
+
+    #[test]
+    fn test_active_as_any() {
+        let mut test = Test::F1(1);
+        assert_eq!(test.active_as_any().and_then(|inner| inner.downcast_ref::<i64>()), Some(&1));
+        *test.active_as_any_mut().and_then(|inner| inner.downcast_mut::<i64>()).expect("Test failed") = 2;
+        assert_eq!(test, Test::F1(2));
+
+        let test = Test::F5(1.0);
+        let inner = test.active_as_any().expect("Test failed");
+        assert_eq!(**inner.downcast_ref::<Tagged<Meters, f64>>().expect("Test failed"), 1.0);
+        assert!(Test::Empty.active_as_any().expect("Test failed").is::<variant_access_Test::Empty>());
+        assert!(Test::F3(1, true).active_as_any().is_none());
+        assert!(Test::F4 { x: true }.active_as_any().is_none());
+    }
+
+    #[test]
+    fn test_get_by_type_id() {
+        let test = Test::F2(String::from("two"));
+        let inner = test.get_by_type_id(TypeId::of::<String>()).expect("Test failed");
+        assert_eq!(inner.downcast_ref::<String>().expect("Test failed"), "two");
+        assert!(test.get_by_type_id(TypeId::of::<i64>()).is_none());
+        assert!(test.get_by_type_id(TypeId::of::<i32>()).is_none());
+    }
+
+    #[test]
+    fn test_from_any() {
+        let mut test = Test::F1(1);
+        test.set_from_any(Box::new(String::from("two"))).expect("Test failed");
+        assert_eq!(test, Test::F2(String::from("two")));
+        test.set_from_any(Box::new((3_i64, true))).expect("Test failed");
+        assert_eq!(test, Test::F3(3, true));
+        test.set_from_any(Box::new(variant_access_Test::F4 { x: false })).expect("Test failed");
+        assert_eq!(test, Test::F4 { x: false });
+        let value = test.set_from_any(Box::new(1_i32)).expect_err("Test failed");
+        assert_eq!(value.downcast_ref::<i32>(), Some(&1));
+        assert_eq!(test, Test::F4 { x: false });
+
+        let value: Box<dyn Any> = Box::new(Tagged::<Meters, _>::new(4.0));
+        assert_eq!(Test::try_from_any(value).expect("Test failed"), Test::F5(4.0));
+        let value: Box<dyn Any> = Box::new(variant_access_Test::Empty);
+        assert_eq!(Test::try_from_any(value).expect("Test failed"), Test::Empty);
+        let _ = Test::try_from_any(Box::new(4.0)).expect_err("Test failed");
+        assert_eq!(Generic::<bool>::try_from_any(Box::new(true)).expect("Test failed"), Generic::F2(true));
+    }
+}
+
+#[cfg(test)]
+mod test_widen_narrow {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Scalar {
+        Int(i64),
+        Float(f64),
+        Pair(i64, bool),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Value {
+        Text(String),
+        Float(f64),
+        Int(i64),
+        Pair(i64, bool),
+    }
+
+    #[test]
+    fn test_widen_from() {
+        assert_eq!(Value::widen_from(Scalar::Int(1)), Value::Int(1));
+        assert_eq!(Value::widen_from(Scalar::Float(2.0)), Value::Float(2.0));
+        assert_eq!(Value::widen_from(Scalar::Pair(3, true)), Value::Pair(3, true));
+    }
+
+    #[test]
+    fn test_narrow_from() {
+        assert_eq!(Scalar::narrow_from(Value::Int(1)), Ok(Scalar::Int(1)));
+        assert_eq!(Scalar::narrow_from(Value::Pair(3, true)), Ok(Scalar::Pair(3, true)));
+        let text = Value::Text(String::from("text"));
+        assert_eq!(Scalar::narrow_from(text), Err(Value::Text(String::from("text"))));
+    }
+}
+
+#[cfg(test)]
+mod test_attributes {
+    use super::*;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Skipped {
+        F1(i64),
+        #[variant_access(skip)]
+        F2(i64),
+        #[variant_access(skip)]
+        F3(String),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    #[variant_access(derive(ContainsVariant, IsVariant, GetVariant))]
+    enum ReadOnly {
+        F1(i64),
+        F2(bool),
+    }
+
+    pub struct Meters;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Length {
+        #[variant_access(tag = Meters)]
+        Meters(f64),
+        #[variant_access(tag)]
+        Feet(f64),
+        #[variant_access(tag)]
+        Point(f64, f64),
+        Point3(f64, f64, f64),
+        Unknown(f64, f64),
+    }
+
+    #[test]
+    fn test_tag() {
+        let mut test = Length::create_variant_from(Tagged::<Meters, _>::new(1.0));
+        assert_eq!(test, Length::Meters(1.0));
+        assert!(test.has_variant::<Tagged<Meters, f64>>());
+        assert!(!test.has_variant::<f64>());
+        assert!(test
+            .contains_variant::<Tagged<Meters, f64>>()
+            .expect("Test failed"));
+        assert!(!test
+            .contains_variant::<Tagged<variant_access_Length::Feet, f64>>()
+            .expect("Test failed"));
+
+        let inner: &mut Tagged<Meters, f64> = test.get_variant_mut().expect("Test failed");
+        **inner += 1.0;
+        let inner: &Tagged<Meters, f64> = test.get_variant_ref().expect("Test failed");
+        assert_eq!(**inner, 2.0);
+        let result: Result<&Tagged<variant_access_Length::Feet, f64>, _> = test.get_variant_ref();
+        let _ = result.expect_err("Test failed");
+
+        test.set_variant(Tagged::<variant_access_Length::Feet, _>::new(3.0));
+        assert_eq!(test, Length::Feet(3.0));
+        let inner: Tagged<variant_access_Length::Feet, f64> = test.get_variant().expect("Test failed");
+        assert_eq!(inner.into_inner(), 3.0);
+
+        let mut test = Length::create_variant_from(Tagged::<variant_access_Length::Point, _>::new((1.0, 2.0)));
+        let inner: Tagged<variant_access_Length::Point, (&mut f64, &mut f64)> =
+            (&mut test).get_variant().expect("Test failed");
+        *inner.value.1 = 3.0;
+        let inner: Tagged<variant_access_Length::Point, (&f64, &f64)> = (&test).get_variant().expect("Test failed");
+        assert_eq!(inner.into_inner(), (&1.0, &3.0));
+
+        test.set_variant((1.0, 2.0));
+        assert_eq!(test, Length::Unknown(1.0, 2.0));
+        test.set_variant((1.0, 2.0, 3.0));
+        assert_eq!(test, Length::Point3(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut test = Skipped::F2(1);
+        assert!(test.has_variant::<i64>());
+        assert!(!test.has_variant::<String>());
+        assert!(!test.contains_variant::<i64>().expect("Test failed"));
+        let _ = test.contains_variant::<String>().expect_err("Test failed");
+        let _ = GetVariantRef::<i64, _>::get_variant_ref(&test).expect_err("Test failed");
+
+        test.set_variant(2_i64);
+        assert_eq!(test, Skipped::F1(2));
+        assert!(test.contains_variant::<i64>().expect("Test failed"));
+
+        let test = Skipped::F3(String::from("skipped"));
+        assert!(!test.contains_variant::<i64>().expect("Test failed"));
+        // the type of a skipped variant is not a field type, even while it is active
+        let error = test.contains_variant::<String>().expect_err("Test failed");
+        assert_eq!(error.kind(), ErrorKind::InvalidType);
+    }
+
+    #[test]
+    fn test_derive_list() {
+        let test = ReadOnly::F2(true);
+        assert!(test.contains_variant::<bool>().expect("Test failed"));
+        assert!(IsVariant::<bool, _>::is_variant(&test));
+        let inner: &bool = test.get_variant_ref().expect("Test failed");
+        assert!(*inner);
+        let test = ReadOnly::F1(1);
+        assert!(!IsVariant::<bool, _>::is_variant(&test));
+    }
+}
+
 #[cfg(test)]
 mod test_namespaces {
     use super::*;
@@ -206,18 +1568,29 @@ mod test_compile_failures {
     #[test]
     fn test_uncompilable_examples() {
         let t = trybuild::TestCases::new();
+        t.compile_fail("tests/uncompilable_examples/aliased_field_type.rs");
         t.compile_fail("tests/uncompilable_examples/bad_func_create_variant_from_type.rs");
         t.compile_fail("tests/uncompilable_examples/bad_get_variant_type.rs");
+        t.compile_fail("tests/uncompilable_examples/bad_tag.rs");
         t.compile_fail("tests/uncompilable_examples/bad_trait_create_variant_from_type.rs");
-        t.compile_fail("tests/uncompilable_examples/enum_with_named_subfields.rs");
-        t.compile_fail("tests/uncompilable_examples/enum_with_tuple_field.rs");
+        t.compile_fail("tests/uncompilable_examples/contains_any_foreign_type.rs");
+        t.compile_fail("tests/uncompilable_examples/contains_foreign_type.rs");
         t.compile_fail("tests/uncompilable_examples/get_wrong_variant_generics.rs");
         t.compile_fail("tests/uncompilable_examples/input_not_enum.rs");
-        t.compile_fail("tests/uncompilable_examples/non_static_lifetime_parameter.rs");
+        t.compile_fail("tests/uncompilable_examples/match_variant_extra_type.rs");
+        t.compile_fail("tests/uncompilable_examples/match_variant_missing_type.rs");
+        t.compile_fail("tests/uncompilable_examples/multiple_errors.rs");
         t.compile_fail("tests/uncompilable_examples/non_static_templates.rs");
+        t.compile_fail("tests/uncompilable_examples/set_from_any_on_read_only.rs");
+        t.compile_fail("tests/uncompilable_examples/set_variant_on_read_only.rs");
+        t.compile_fail("tests/uncompilable_examples/try_from_any_on_read_only.rs");
         t.compile_fail("tests/uncompilable_examples/twice_used_field_type.rs");
         t.compile_fail("tests/uncompilable_examples/type_does_not_implement_debug.rs");
         t.compile_fail("tests/uncompilable_examples/type_does_not_implement_partialeq.rs");
+        t.compile_fail("tests/uncompilable_examples/unknown_attribute_argument.rs");
+        t.compile_fail("tests/uncompilable_examples/visit2_missing_combination.rs");
+        t.compile_fail("tests/uncompilable_examples/visit_with_skipped_variant.rs");
+        t.compile_fail("tests/uncompilable_examples/widen_from_wider_enum.rs");
     }
 }
 
@@ -369,3 +1742,140 @@ mod test_template_types {
         );
     }
 }
+
+#[cfg(test)]
+mod test_full_generics {
+    use super::*;
+    use std::fmt::Debug;
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Buf<const N: usize> {
+        Small([u8; N]),
+        Big(Vec<u8>),
+    }
+
+    #[derive(VariantAccess, PartialEq, Debug)]
+    enum Defaulted<T: 'static + Clone = i32, U: 'static = bool>
+    where
+        T: Debug,
+    {
+        F1(T),
+        F2(Vec<U>),
+        F3 { value: T, count: usize },
+    }
+
+    #[test]
+    fn test_const_generics() {
+        let mut buf = Buf::<4>::Small([1, 2, 3, 4]);
+        assert!(buf.has_variant::<[u8; 4]>());
+        assert!(buf.contains_variant::<[u8; 4]>().expect("Test failed"));
+        let inner: &mut [u8; 4] = buf.get_variant_mut().expect("Test failed");
+        inner[0] = 0;
+        assert_eq!(buf, Buf::Small([0, 2, 3, 4]));
+        buf.set_variant(vec![1_u8]);
+        assert_eq!(buf, Buf::Big(vec![1]));
+        let buf = Buf::<2>::create_variant_from([1_u8, 2]);
+        let inner: [u8; 2] = buf.get_variant().expect("Test failed");
+        assert_eq!(inner, [1, 2]);
+    }
+
+    #[test]
+    fn test_defaults_and_where_clauses() {
+        let mut test: Defaulted = Defaulted::F1(1);
+        assert!(test.contains_variant::<i32>().expect("Test failed"));
+        test.set_variant(vec![true]);
+        assert_eq!(test, Defaulted::F2(vec![true]));
+        test.set_variant(variant_access_Defaulted::F3 { value: 2, count: 1 });
+        let variant_access_Defaulted::F3Ref { value, count } = (&test).get_variant().expect("Test failed");
+        assert_eq!((*value, *count), (2, 1));
+        let test = Defaulted::<String>::create_variant_from(String::from("x"));
+        let inner: &String = test.get_variant_ref().expect("Test failed");
+        assert_eq!(inner, "x");
+    }
+}
+
+#[cfg(test)]
+mod test_type_grammar {
+    use super::*;
+
+    const LEN: usize = 2;
+
+    pub trait Shape {
+        type Output;
+    }
+
+    pub struct Square;
+
+    impl Shape for Square {
+        type Output = f64;
+    }
+
+    #[allow(unused_parens)]
+    #[derive(VariantAccess)]
+    enum Test {
+        F1(&'static str),
+        F2(&'static [u8]),
+        F3(fn(i32) -> i32),
+        F4(Box<(dyn Fn(i32) -> i32 + Send)>),
+        F5(<Square as Shape>::Output),
+        F6([u8; LEN]),
+        F7([i8; LEN * 2]),
+        F8(std::convert::Infallible),
+    }
+
+    macro_rules! make_enum {
+        ($name:ident, $first:ty, $second:ty) => {
+            #[allow(unused_parens)]
+            #[derive(VariantAccess, PartialEq, Debug)]
+            enum $name {
+                F1($first),
+                F2(($second)),
+            }
+        };
+    }
+
+    make_enum!(Generated, i64, Vec<bool>);
+
+    fn double(x: i32) -> i32 {
+        2 * x
+    }
+
+    #[test]
+    #[allow(clippy::borrowed_box)]
+    fn test_borrowed_and_pointer_types() {
+        let mut test = Test::create_variant_from("static");
+        assert!(test.has_variant::<&str>());
+        let inner: &&str = test.get_variant_ref().expect("Test failed");
+        assert_eq!(*inner, "static");
+        test.set_variant(&b"bytes"[..]);
+        assert!(test.contains_variant::<&[u8]>().expect("Test failed"));
+        test.set_variant(double as fn(i32) -> i32);
+        let inner: fn(i32) -> i32 = test.get_variant().expect("Test failed");
+        assert_eq!(inner(2), 4);
+        let test = Test::create_variant_from(Box::new(double) as Box<dyn Fn(i32) -> i32 + Send>);
+        let inner: &Box<dyn Fn(i32) -> i32 + Send> = test.get_variant_ref().expect("Test failed");
+        assert_eq!(inner(3), 6);
+    }
+
+    #[test]
+    fn test_qualified_paths_and_array_lengths() {
+        let mut test = Test::create_variant_from(1.0_f64);
+        assert!(test.contains_variant::<f64>().expect("Test failed"));
+        assert!(test.has_variant::<std::convert::Infallible>());
+        test.set_variant([1_u8, 2]);
+        let inner: &[u8; 2] = test.get_variant_ref().expect("Test failed");
+        assert_eq!(inner, &[1, 2]);
+        test.set_variant([1_i8; 4]);
+        assert!(test.contains_variant::<[i8; 4]>().expect("Test failed"));
+    }
+
+    #[test]
+    fn test_macro_generated_enum() {
+        let mut test = Generated::create_variant_from(1_i64);
+        assert!(test.contains_variant::<i64>().expect("Test failed"));
+        test.set_variant(vec![true]);
+        assert_eq!(test, Generated::F2(vec![true]));
+        let inner: Vec<bool> = test.get_variant().expect("Test failed");
+        assert_eq!(inner, vec![true]);
+    }
+}
@@ -1,9 +1,12 @@
 use variant_access_derive::*;
 
+type Alias = i64;
+
 #[derive(VariantAccess)]
 enum Enum {
-    F1{a: i64, b: bool},
-    F2(bool)
+    F1(i64),
+    F2(Alias),
+    F3(bool)
 }
 
 fn main() {
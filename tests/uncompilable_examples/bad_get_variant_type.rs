@@ -9,5 +9,5 @@ enum Enum {
 
 fn main() {
     let instance = Enum::F1(42);
-    let _: Result<&i32, VariantAccessError> = instance.get_variant();
+    let _: Result<&i32, VariantAccessError<Enum>> = instance.get_variant();
 }
@@ -0,0 +1,14 @@
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Enum {
+     F1(f64),
+     #[variant_access(tag)]
+     F2 { x: f64 },
+     #[variant_access(tag)]
+     F3,
+     #[variant_access(tag = 42)]
+     F4(f64)
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Test {
+     F1(i64),
+     F2(bool)
+}
+
+fn main() {
+    let value = Test::F1(1);
+    let _ = value.contains::<i32>();
+}
@@ -0,0 +1,17 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Test {
+     F1(i32),
+     F2(bool),
+     F3(String)
+}
+
+fn main() {
+    let value = Test::F1(1);
+    let _ = match_variant!(value {
+        x: i32 => x,
+        b: bool => b as i32,
+    });
+}
@@ -0,0 +1,14 @@
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Enum {
+    F1 { x: i64 },
+    F1Ref(bool),
+    F2(bool),
+    F3(i32),
+    F4(std::primitive::i32),
+}
+
+fn main() {
+
+}
@@ -0,0 +1,14 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+#[variant_access(derive(ContainsVariant, GetVariant))]
+enum Enum {
+     F1(i64),
+     F2(bool)
+}
+
+fn main() {
+    let mut instance = Enum::F1(42);
+    instance.set_variant(false);
+}
@@ -0,0 +1,12 @@
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+#[variant_access(derive(ContainsVariant, GetVariant))]
+enum Enum {
+     F1(i64),
+     F2(bool)
+}
+
+fn main() {
+    let _ = Enum::try_from_any(Box::new(5_i64));
+}
@@ -0,0 +1,12 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+#[variant_access(derive(GetVariant, TakeVariant))]
+enum Enum {
+     F1(i64),
+     #[variant_access(hide)]
+     F2(bool)
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Operand {
+     Int(i64),
+     Float(f64)
+}
+
+struct Add;
+
+impl Visitor for Add {
+    type Output = f64;
+}
+
+impl VisitPair<&i64, &i64> for Add {
+    fn visit_pair(self, first: &i64, second: &i64) -> f64 {
+        (first + second) as f64
+    }
+}
+
+impl VisitPair<&f64, &f64> for Add {
+    fn visit_pair(self, first: &f64, second: &f64) -> f64 {
+        first + second
+    }
+}
+
+fn main() {
+    let _ = visit2(&Operand::Int(1), &Operand::Float(0.5), Add);
+}
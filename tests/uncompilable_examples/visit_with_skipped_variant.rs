@@ -0,0 +1,12 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+#[variant_access(derive(GetVariant, Visit))]
+enum Enum {
+     F1(i64),
+     #[variant_access(skip)]
+     F2(i64)
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use variant_access_traits::*;
+use variant_access_derive::*;
+
+#[derive(VariantAccess)]
+enum Scalar {
+     Int(i64),
+     Float(f64)
+}
+
+#[derive(VariantAccess)]
+enum Value {
+     Int(i64),
+     Float(f64),
+     Text(String)
+}
+
+fn main() {
+    let _ = Scalar::widen_from(Value::Int(1));
+}
@@ -0,0 +1,248 @@
+use super::*;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+/// The traits that can be listed in `#[variant_access(derive(...))]`
+/// on the enum. ContainsVariant includes the inherent methods borrowing
+/// the active field as `dyn Any`, and IsVariant includes HasVariant,
+/// `contains` and `contains_any`. GetVariant includes GetVariantRef,
+/// the impls on references to the enum and the inherent methods taking
+/// a type, except for `emplace_with` and `set_from_any` which
+/// SetVariant includes. CreateVariantFrom includes `try_from_any`.
+/// These three include VariantMarker. VariantAt includes VariantAtRef
+/// and the inherent methods taking an index, and Visit includes
+/// VisitMut and IntoVisit.
+pub const TRAITS: [&str; 10] = [
+    "ContainsVariant",
+    "ActiveVariant",
+    "VariantTypes",
+    "IsVariant",
+    "GetVariant",
+    "SetVariant",
+    "CreateVariantFrom",
+    "VariantAt",
+    "Visit",
+    "Alternatives",
+];
+
+/// An argument of the `#[variant_access(...)]` attribute, of one of
+/// the forms `name`, `name(A, B, ...)` or `name = Type`.
+pub enum AttributeArg {
+    Flag(Ident),
+    List(Ident, Vec<Ident>),
+    Value(Ident, Box<syn::Type>),
+}
+
+impl AttributeArg {
+    fn name(&self) -> &Ident {
+        match self {
+            AttributeArg::Flag(name) | AttributeArg::List(name, _) | AttributeArg::Value(name, _) => name,
+        }
+    }
+}
+
+impl Parse for AttributeArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            let items = Punctuated::<Ident, syn::Token![,]>::parse_terminated(&content)?;
+            Ok(AttributeArg::List(name, items.into_iter().collect()))
+        } else if input.peek(syn::Token![=]) {
+            input.parse::<syn::Token![=]>()?;
+            let value = input.parse().map_err(|error| {
+                syn::Error::new(error.span(), format!("expected `{0}` or `{0} = Type`", name))
+            })?;
+            Ok(AttributeArg::Value(name, Box::new(value)))
+        } else {
+            Ok(AttributeArg::Flag(name))
+        }
+    }
+}
+
+/// Collects the arguments of all `#[variant_access(...)]` attributes
+/// among the given ones.
+fn parse_attribute_args(attrs: &[syn::Attribute]) -> syn::Result<Vec<AttributeArg>> {
+    let mut args = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("variant_access")) {
+        args.extend(attr.parse_args_with(Punctuated::<AttributeArg, syn::Token![,]>::parse_terminated)?);
+    }
+    Ok(args)
+}
+
+/// The error for an argument not accepted in the given position
+fn unknown_arg_error(arg: &AttributeArg, position: &str, expected: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        arg.name(),
+        format!(
+            "unknown argument `{}` to `#[variant_access]` on {}; expected {}",
+            arg.name(),
+            position,
+            expected
+        ),
+    )
+}
+
+/// The options given by `#[variant_access(...)]` on the enum.
+///
+/// # Example
+/// ```ignore
+/// #[derive(VariantAccess)]
+/// #[variant_access(derive(ContainsVariant, GetVariant))]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// ```
+/// only implements ContainsVariant and GetVariant, so that
+/// `set_variant` cannot be called on such an enum.
+///
+/// # Example
+/// ```ignore
+/// #[derive(VariantAccess)]
+/// #[variant_access(from, try_into)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// ```
+/// also implements `From<i64>` and `From<bool>` for Enum, as well as
+/// `TryFrom<Enum>` for i64 and bool.
+///
+/// If any field type depends on the generic parameters of the enum,
+/// the fields whose types do so are silently left out of both, as
+/// they could be the same type as another field, e.g. a field of type
+/// `T` would conflict with one of type i64 for `Enum<i64>`.
+pub struct EnumOptions {
+    /// The traits to derive, or None for all of them
+    derive: Option<Vec<Ident>>,
+    /// Whether to implement From for the type of each convertible field
+    pub from: bool,
+    /// Whether to implement TryFrom the enum for the type of each
+    /// convertible field
+    pub try_into: bool,
+}
+
+impl EnumOptions {
+    /// Whether the trait of the given name should be derived
+    pub fn derives(&self, trait_: &str) -> bool {
+        match &self.derive {
+            Some(traits) => traits.iter().any(|ident| ident == trait_),
+            None => true,
+        }
+    }
+
+    /// Where the trait of the given name was requested, if it was
+    /// listed explicitly
+    pub fn requested(&self, trait_: &str) -> Option<&Ident> {
+        self.derive
+            .iter()
+            .flatten()
+            .find(|ident| *ident == trait_)
+    }
+}
+
+/// Reads the options of the enum, validating that only known traits
+/// are listed.
+pub fn fetch_enum_options(ast: &DeriveInput) -> syn::Result<EnumOptions> {
+    let mut options = EnumOptions {
+        derive: None,
+        from: false,
+        try_into: false,
+    };
+    for arg in parse_attribute_args(&ast.attrs)? {
+        match &arg {
+            AttributeArg::List(name, traits) if name == "derive" => {
+                if let Some(unknown) = traits.iter().find(|ident| !TRAITS.iter().any(|trait_| *ident == trait_)) {
+                    return Err(syn::Error::new_spanned(
+                        unknown,
+                        format!(
+                            "VariantAccess cannot derive `{}`; expected one of {}",
+                            unknown,
+                            TRAITS.join(", ")
+                        ),
+                    ));
+                }
+                options.derive.get_or_insert_with(Vec::new).extend(traits.iter().cloned());
+            }
+            AttributeArg::Flag(name) if name == "from" => options.from = true,
+            AttributeArg::Flag(name) if name == "try_into" => options.try_into = true,
+            _ => return Err(unknown_arg_error(&arg, "an enum", "`derive(...)`, `from` or `try_into`")),
+        }
+    }
+    Ok(options)
+}
+
+/// The options given by `#[variant_access(...)]` on a variant.
+///
+/// # Example
+/// ```ignore
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     #[variant_access(skip)]
+///     F2(i64)
+/// }
+/// ```
+/// does not expose F2, so its type does not clash with that of F1.
+///
+/// # Example
+/// ```ignore
+/// struct Meters;
+///
+/// #[derive(VariantAccess)]
+/// enum Length {
+///     #[variant_access(tag = Meters)]
+///     Meters(f64),
+///     #[variant_access(tag)]
+///     Feet(f64)
+/// }
+/// ```
+/// accesses Meters as `Tagged<Meters, f64>` and Feet as
+/// `Tagged<variant_access_Length::Feet, f64>`.
+pub struct VariantOptions {
+    pub skip: bool,
+    pub tag: Option<TagOption>,
+}
+
+/// The tag given by `#[variant_access(tag)]` or `#[variant_access(tag = Type)]`
+pub struct TagOption {
+    /// The `tag` keyword, for reporting errors
+    pub keyword: Ident,
+    /// The tag type, or None for the marker struct of the variant
+    pub ty: Option<syn::Type>,
+}
+
+/// Reads the options of a variant
+pub fn fetch_variant_options(var: &syn::Variant) -> syn::Result<VariantOptions> {
+    let mut options = VariantOptions { skip: false, tag: None };
+    for arg in parse_attribute_args(&var.attrs)? {
+        match arg {
+            AttributeArg::Flag(name) if name == "skip" => options.skip = true,
+            AttributeArg::Flag(name) if name == "tag" => {
+                options.tag = Some(TagOption { keyword: name, ty: None })
+            }
+            AttributeArg::Value(name, ty) if name == "tag" => {
+                options.tag = Some(TagOption {
+                    keyword: name,
+                    ty: Some(*ty),
+                })
+            }
+            _ => return Err(unknown_arg_error(&arg, "a variant", "`skip`, `tag` or `tag = Type`")),
+        }
+    }
+    Ok(options)
+}
+
+/// The first variant marked with `#[variant_access(skip)]`, if any
+pub fn fetch_skipped_variant(ast: &DeriveInput) -> Option<&Ident> {
+    match &ast.data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .find(|var| fetch_variant_options(var).is_ok_and(|options| options.skip))
+            .map(|var| &var.ident),
+        _ => None,
+    }
+}
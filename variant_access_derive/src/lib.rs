@@ -1,8 +1,10 @@
+mod attributes;
+mod match_variant;
 mod parse_enum;
-mod templates;
 
+use crate::attributes::*;
+use crate::match_variant::*;
 use crate::parse_enum::*;
-use crate::templates::*;
 
 extern crate proc_macro;
 
@@ -12,22 +14,43 @@ use std::any::type_name;
 use std::iter::Enumerate;
 
 use proc_macro::TokenStream;
-use quote::ToTokens;
-use std::collections::HashMap;
-use syn::{self, Data, DeriveInput, GenericParam, Ident};
-use tera::*;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{self, spanned::Spanned, Data, DeriveInput, GenericParam, Ident};
 
-#[proc_macro_derive(VariantAccess)]
+#[proc_macro_derive(VariantAccess, attributes(variant_access))]
 pub fn variant_access_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as DeriveInput);
     impl_variant_access(&ast)
 }
 
+/// Matches on the type of the active field of an enum, rather than
+/// on the names of its variants. Each arm binds the value of the
+/// field of the given type, and the matched value is moved into the
+/// arm that is taken.
+///
+/// Unless there is a final `_` arm, compilation fails if the arms do
+/// not handle each alternative of the enum exactly once. An arm for
+/// a type that is not an alternative of the enum never compiles.
+///
+/// # Example
+/// ```ignore
+/// let description = match_variant!(instance {
+///     x: i64 => format!("number {}", x),
+///     b: bool => format!("flag {}", b),
+/// });
+/// ```
+#[proc_macro]
+pub fn match_variant(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as MatchVariant);
+    expand_match_variant(input).into()
+}
+
 /// Implements ContainsVariant trait that determines
 /// if one of the enum fields contains the input type
 ///
 /// # Example
-/// ```
+/// ```ignore
 ///enum Enum {
 ///    F1(i64),
 ///    F2(bool)
@@ -40,116 +63,1006 @@ pub fn variant_access_derive(input: TokenStream) -> TokenStream {
 /// let result = instance.contains_variant::<bool>(); // result has value Ok(false)
 /// let result = instance.contains_variant::<i32>(); // result has value Err
 /// ```
-fn impl_contains_variant(
+fn impl_contains_variant(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let generic = fresh_generic(params, "Variant");
+    let field_types: Vec<TokenStream2> = types.iter().map(VariantFields::ty).collect();
+    let error = access_error(ast, "invalid_type", &generic.to_token_stream(), quote! { self });
+    let branches = types.iter().map(|fields| {
+        let field = fields.ident;
+        let type_ = fields.ty();
+        quote! {
+            #ident::#field { .. } => Ok(std::any::TypeId::of::<#type_>() == std::any::TypeId::of::<#generic>())
+        }
+    });
+
+    quote! {
+        impl #impl_generics variant_access_traits::ContainsVariant for #ident #ty_generics #where_clause {
+            fn has_variant<#generic: 'static>(&self) -> bool {
+                #(
+                    if std::any::TypeId::of::<#generic>() == std::any::TypeId::of::<#field_types>() {
+                        return true;
+                    }
+                )*
+                false
+            }
+
+            fn contains_variant<#generic: 'static>(&self) -> Result<bool, variant_access_traits::VariantAccessError> {
+                if self.has_variant::<#generic>() {
+                    match self {
+                        #(#branches,)*
+                        _ => Ok(false)
+                    }
+                } else {
+                    Err(#error)
+                }
+            }
+        }
+    }
+}
+
+/// Implements inherent methods borrowing the active field as `dyn Any`,
+/// for code that only knows the `TypeId` of the field. As with
+/// GetVariantRef, only single fields and the marker structs of unit
+/// variants can be borrowed, so these return None while a variant with
+/// several or named fields, or a skipped variant, is active.
+///
+/// # Example
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool),
+///     F3(i64, bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let inner = instance.active_as_any().unwrap().downcast_ref::<i64>(); // assigns Some(&42) to inner
+/// let inner = instance.get_by_type_id(TypeId::of::<bool>()); // assigns None to inner
+/// let inner = Enum::F3(1, true).active_as_any(); // assigns None to inner
+/// ```
+fn impl_any_methods(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let borrowable = || types.iter().filter(|fields| fields.is_single() || fields.is_unit());
+    let ref_branches = borrowable().map(|fields| {
+        let field = fields.ident;
+        let pattern = fields.pattern();
+        let ref_value = fields.borrowed_value(false);
+        quote! {
+            #ident::#field #pattern => Some(#ref_value)
+        }
+    });
+    let mut_branches = borrowable().map(|fields| {
+        let field = fields.ident;
+        let pattern = fields.pattern();
+        let mut_value = fields.borrowed_value(true);
+        quote! {
+            #ident::#field #pattern => Some(#mut_value)
+        }
+    });
+    let type_id_branches = borrowable().map(|fields| {
+        let field = fields.ident;
+        let type_ = fields.ty();
+        let pattern = fields.pattern();
+        let ref_value = fields.borrowed_value(false);
+        quote! {
+            #ident::#field #pattern if std::any::TypeId::of::<#type_>() == type_id => Some(#ref_value)
+        }
+    });
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Borrows the active field as `dyn Any`. Returns None if the
+            /// active variant has several or named fields, as these are not
+            /// stored as one value, or if it is skipped.
+            #[allow(dead_code)]
+            pub fn active_as_any(&self) -> Option<&dyn std::any::Any> {
+                match self {
+                    #(#ref_branches,)*
+                    #[allow(unreachable_patterns)]
+                    _ => None
+                }
+            }
+
+            /// Mutably borrows the active field as `dyn Any`. Returns None in
+            /// the same cases as `active_as_any`.
+            #[allow(dead_code)]
+            pub fn active_as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+                match self {
+                    #(#mut_branches,)*
+                    #[allow(unreachable_patterns)]
+                    _ => None
+                }
+            }
+
+            /// Borrows the active field as `dyn Any` if its type has the
+            /// given `TypeId`. Returns None otherwise, and in the same cases
+            /// as `active_as_any`.
+            #[allow(dead_code)]
+            pub fn get_by_type_id(&self, type_id: std::any::TypeId) -> Option<&dyn std::any::Any> {
+                match self {
+                    #(#type_id_branches,)*
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+/// Tries to downcast `value` to the type of each field in turn. On
+/// success, the variant built from it is passed to `found`, whose
+/// result is returned. Otherwise `value` is left holding the box.
+fn any_downcasts(
     ast: &DeriveInput,
-    name: &str,
-    params: &[String],
-    types: &HashMap<String, &Ident>,
-    templater: &Tera,
-) -> TokenStream {
-    // generic is a parameter name guaranteed not to be equal to the enum generic parameter names
-    let (param_string, generic) = if !params.is_empty() {
-        (
-            format!("<{}>", ast.generics.params.to_token_stream()),
-            params.concat(),
+    types: &[VariantFields<'_>],
+    value: &Ident,
+    found: impl Fn(TokenStream2) -> TokenStream2,
+) -> TokenStream2 {
+    let ident = &ast.ident;
+    types
+        .iter()
+        .map(|fields| {
+            let field = fields.ident;
+            let type_ = fields.ty();
+            let construct = fields.construct(value);
+            let found = found(quote! { #ident::#field #construct });
+            quote! {
+                let #value = match #value.downcast::<#type_>() {
+                    Ok(#value) => {
+                        let #value = *#value;
+                        return #found;
+                    }
+                    Err(#value) => #value,
+                };
+            }
+        })
+        .collect()
+}
+
+/// Implements the inherent method `set_from_any`, setting the active
+/// field from a boxed value of the type of any field.
+///
+/// # Example
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let mut instance = Enum::F1(42);
+///
+/// instance.set_from_any(Box::new(true)).unwrap(); // instance is now equal to Enum::F2(true)
+/// let result = instance.set_from_any(Box::new(1_i32)); // assigns Err(Box::new(1_i32)) to result
+/// ```
+fn impl_set_from_any(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    let downcasts = any_downcasts(ast, types, &value, |variant| quote! { { *self = #variant; Ok(()) } });
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Sets the active field to the boxed value, or gives it back if no
+            /// field has its type
+            #[allow(dead_code)]
+            pub fn set_from_any(
+                &mut self,
+                #value: std::boxed::Box<dyn std::any::Any>,
+            ) -> Result<(), std::boxed::Box<dyn std::any::Any>> {
+                #downcasts
+                Err(#value)
+            }
+        }
+    }
+}
+
+/// Implements the inherent function `try_from_any`, creating the enum
+/// from a boxed value of the type of any field.
+///
+/// # Example
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// let instance = Enum::try_from_any(Box::new(true)); // assigns Ok(Enum::F2(true)) to instance
+/// let instance = Enum::try_from_any(Box::new(1_i32)); // assigns Err(Box::new(1_i32)) to instance
+/// ```
+fn impl_try_from_any(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    let downcasts = any_downcasts(ast, types, &value, |variant| quote! { Ok(#variant) });
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Creates the enum from the boxed value, trying the type of each
+            /// field in turn, or gives it back if no field has its type
+            #[allow(dead_code)]
+            pub fn try_from_any(
+                #value: std::boxed::Box<dyn std::any::Any>,
+            ) -> Result<Self, std::boxed::Box<dyn std::any::Any>> {
+                #downcasts
+                Err(#value)
+            }
+        }
+    }
+}
+
+/// Implements the ActiveVariant trait describing the active field.
+/// Indices follow the declaration of the enum, including variants
+/// skipped with `#[variant_access(skip)]`, which are described by
+/// the tuple of their field types.
+///
+/// # Example
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// let instance = Enum::F2(true);
+/// let result = instance.variant_index(); // assigns 1 to result
+/// let result = instance.variant_name(); // assigns "F2" to result
+/// let result = instance.active_type_name(); // assigns "bool" to result
+/// ```
+fn impl_active_variant(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let (fields, types) = fetch_active_types(ast, types);
+    let indices = 0..fields.len();
+    let names = fields.iter().map(|field| field.to_string());
+
+    quote! {
+        impl #impl_generics variant_access_traits::ActiveVariant for #ident #ty_generics #where_clause {
+            fn variant_index(&self) -> usize {
+                match *self {
+                    #(#ident::#fields { .. } => #indices,)*
+                }
+            }
+
+            fn variant_name(&self) -> &'static str {
+                match *self {
+                    #(#ident::#fields { .. } => #names,)*
+                }
+            }
+
+            fn active_type_name(&self) -> &'static str {
+                match *self {
+                    #(#ident::#fields { .. } => std::any::type_name::<#types>(),)*
+                }
+            }
+
+            fn active_type_id(&self) -> std::any::TypeId {
+                match *self {
+                    #(#ident::#fields { .. } => std::any::TypeId::of::<#types>(),)*
+                }
+            }
+        }
+    }
+}
+
+/// The name and type of each variant of the enum, including skipped
+/// variants, which are described by the tuple of their field types.
+fn fetch_active_types<'a>(ast: &'a DeriveInput, types: &[VariantFields<'_>]) -> (Vec<&'a Ident>, Vec<TokenStream2>) {
+    let variants = match &ast.data {
+        Data::Enum(data) => &data.variants,
+        _ => return (vec![], vec![]),
+    };
+    variants
+        .iter()
+        .map(|var| match types.iter().find(|fields| fields.ident == &var.ident) {
+            Some(fields) => (&var.ident, fields.ty()),
+            None => {
+                let (_, ty_generics, _) = ast.generics.split_for_impl();
+                let ident = &ast.ident;
+                let self_type = quote! { #ident #ty_generics };
+                let field_types = var
+                    .fields
+                    .iter()
+                    .map(|field| replace_self(field.ty.to_token_stream(), &self_type));
+                (&var.ident, quote! { (#(#field_types),*) })
+            }
+        })
+        .unzip()
+}
+
+/// Defines a function giving the name of the type of the active field,
+/// with which the errors of the generated impls are made. It is
+/// defined alongside the impls, so it is not visible outside of them.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// ```
+/// produces
+/// ```ignore
+/// fn active_type_name(value: &Enum) -> &'static str {
+///     match *value {
+///         Enum::F1 { .. } => std::any::type_name::<i64>(),
+///         Enum::F2 { .. } => std::any::type_name::<bool>(),
+///     }
+/// }
+/// ```
+fn define_active_type_name(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let (fields, types) = fetch_active_types(ast, types);
+    // The generics of the enum may have bounds both inline and in the where clause
+    quote! {
+        #[allow(dead_code, clippy::multiple_bound_locations)]
+        fn active_type_name #impl_generics(value: &#ident #ty_generics) -> &'static str #where_clause {
+            match *value {
+                #(#ident::#fields { .. } => std::any::type_name::<#types>(),)*
+            }
+        }
+    }
+}
+
+/// The error of the given kind for a request of the given type, made
+/// from a reference to the enum
+fn access_error(ast: &DeriveInput, kind: &str, requested: &TokenStream2, value: TokenStream2) -> TokenStream2 {
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let constructor = Ident::new(kind, Span::call_site());
+    quote! {
+        variant_access_traits::VariantAccessError::#constructor(
+            std::any::type_name::<#ident #ty_generics>(),
+            std::any::type_name::<#requested>(),
+            active_type_name(#value),
         )
+    }
+}
+
+/// A generic parameter name guaranteed not to be equal to the enum generic
+/// parameter names, as it is longer than each of them
+fn fresh_generic(params: &[String], suffix: &str) -> Ident {
+    let params = params
+        .iter()
+        .filter(|param| !param.starts_with('\''))
+        .cloned()
+        .collect::<String>();
+    format_ident!("{}{}", params, suffix)
+}
+
+/// Implements the VariantTypes trait listing the alternatives of
+/// the enum, in the order they are declared. Skipped variants are
+/// not among them.
+///
+/// # Example
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// let result = Enum::type_names(); // assigns vec!["i64", "bool"] to result
+/// let result = Enum::index_of::<bool>(); // assigns Some(1) to result
+/// ```
+fn impl_variant_types(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let count = types.len();
+    let field_types: Vec<TokenStream2> = types.iter().map(VariantFields::ty).collect();
+    let names = types.iter().map(|fields| fields.ident.to_string());
+
+    quote! {
+        impl #impl_generics variant_access_traits::VariantTypes for #ident #ty_generics #where_clause {
+            const VARIANT_COUNT: usize = #count;
+
+            fn type_ids() -> std::vec::Vec<std::any::TypeId> {
+                std::vec![#(std::any::TypeId::of::<#field_types>()),*]
+            }
+
+            fn type_names() -> std::vec::Vec<&'static str> {
+                std::vec![#(std::any::type_name::<#field_types>()),*]
+            }
+
+            fn variant_names() -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+        }
+    }
+}
+
+/// Checks at compile time that no two variants have the same type,
+/// for those types the derive macro cannot tell apart itself, such as
+/// type aliases. A trait is implemented for the type of each variant,
+/// naming its marker struct. If two of these are identical, compilation
+/// fails with conflicting implementations of the trait, pointing at
+/// both variants. VariantMarker and HasVariant are implemented for
+/// all checked types at once through this trait, so that they do not
+/// conflict as well.
+///
+/// Types mentioning generic parameters are not checked, as their
+/// impls would conflict regardless. Neither are generated marker
+/// structs, which are distinct by construction.
+///
+/// # Example
+/// ```ignore
+/// type Alias = i64;
+///
+/// enum Enum {
+///     F1(i64),
+///     F2(Alias)
+/// }
+/// ```
+/// fails with "conflicting implementations of trait
+/// `VariantAccess_Enum_has_variants_of_identical_types` for type `i64`".
+fn impl_distinct_types(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (checked, _) = partition_distinct_types(ast, types);
+    if checked.is_empty() {
+        return TokenStream2::new();
+    }
+    let check = distinct_types_check(ast);
+    let impls = checked.iter().map(|fields| {
+        let type_ = fields.ty();
+        let marker = fields.marker();
+        // point at the variant in case of conflicting implementations
+        quote_spanned! { fields.fields.span()=>
+            impl #check for #type_ {
+                type Marker = #marker;
+            }
+        }
+    });
+    // It is public so that its marker can be named by VariantMarker
+    let message = format!("`{{Self}}` is not the type of a field of `{}`", ast.ident);
+    let label = format!("not a field type of `{}`", ast.ident);
+    quote! {
+        #[allow(non_camel_case_types)]
+        #[diagnostic::on_unimplemented(message = #message, label = #label)]
+        pub trait #check {
+            type Marker;
+        }
+        #(#impls)*
+    }
+}
+
+/// Splits the variants into those whose types are checked to be
+/// distinct and the others. None are checked unless there are at
+/// least two of them.
+fn partition_distinct_types<'a, 'b>(
+    ast: &DeriveInput,
+    types: &'b [VariantFields<'a>],
+) -> (Vec<&'b VariantFields<'a>>, Vec<&'b VariantFields<'a>>) {
+    let (checked, unchecked): (Vec<_>, Vec<_>) = types.iter().partition(|fields| {
+        !fields.is_named()
+            && !fields.is_unit()
+            && !mentions_generics(ast, fields.fields.to_token_stream())
+            // impls for the never type are unstable
+            && !fields.type_name().contains('!')
+    });
+    if checked.len() < 2 {
+        (Vec::new(), types.iter().collect())
     } else {
-        (String::from(""), String::from("T"))
-    };
-    let mut context = Context::new();
-    context.insert("generics", &param_string);
-    context.insert("template", &generic);
-    context.insert("fullname", &name);
-    context.insert(
-        "matches",
-        &types
-            .keys()
-            .map(|type_| format!("std::any::TypeId::of::<{}>()", type_))
-            .collect::<Vec<String>>(),
-    );
-
-    context.insert(
-        "branches",
-        &types
-            .iter()
-            .map(|(type_, field_)| {
-                format!(
-                    "{}::{}(_) => Ok(std::any::TypeId::of::<{}>()",
-                    &ast.ident.to_string(),
-                    field_.to_string(),
-                    type_
-                )
-            })
-            .collect::<Vec<String>>(),
-    );
+        (checked, unchecked)
+    }
+}
 
-    let impl_string = templater
-        .render("contains_variant", &context)
-        .expect("Failed to fill in ContainsVariant template");
+/// The trait checking that the types of the variants are distinct
+fn distinct_types_check(ast: &DeriveInput) -> Ident {
+    format_ident!("VariantAccess_{}_has_variants_of_identical_types", ast.ident)
+}
 
-    impl_string.parse().unwrap()
+/// Implements the IsVariant trait for each field type,
+/// determining if the active field is of that type. Unlike
+/// ContainsVariant, this does not rely on `std::any::TypeId`
+/// and so is also implemented for enums with lifetime parameters.
+///
+/// # Example
+/// ```ignore
+/// enum Token<'a> {
+///     Ident(&'a str),
+///     Num(i64)
+/// }
+///
+/// let instance = Token::Ident("x");
+/// let result = IsVariant::<&str, _>::is_variant(&instance); // assigns true to result
+/// let result = IsVariant::<i64, _>::is_variant(&instance); // assigns false to result
+/// ```
+fn impl_is_variant(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    types
+        .iter()
+        .map(|fields| {
+            let type_ = fields.ty();
+            let marker = fields.marker();
+            let field = fields.ident;
+            quote! {
+                impl #impl_generics variant_access_traits::IsVariant<#type_, #marker> for #ident #ty_generics #where_clause {
+                    fn is_variant(&self) -> bool {
+                        matches!(self, #ident::#field { .. })
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Implements HasVariant for the type of each field along with the
+/// inherent method `contains_any`, where the markers can be inferred,
+/// and the inherent method `contains`. Unlike `contains_variant`,
+/// these do not compile for types that are not fields of the enum.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let result = instance.contains::<i64>(); // assigns true to result
+/// let result = instance.contains_any::<(i64, bool)>(); // assigns true to result
+/// // instance.contains::<i32>(); will not compile as Enum has no field of type i32
+/// ```
+fn impl_has_variant(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let mut impls = TokenStream2::new();
+    if markers_inferable(ast, types) {
+        let (checked, unchecked) = partition_distinct_types(ast, types);
+        if !checked.is_empty() {
+            let check = distinct_types_check(ast);
+            let type_ = fresh_generic(params, "Type");
+            let mut generics = ast.generics.clone();
+            generics.params.push(syn::parse_quote! { #type_: #check });
+            generics.make_where_clause().predicates.push(syn::parse_quote! {
+                Self: variant_access_traits::IsVariant<#type_, <#type_ as #check>::Marker>
+            });
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            impls.extend(quote! {
+                impl #impl_generics variant_access_traits::HasVariant<#type_> for #ident #ty_generics #where_clause {}
+            });
+        }
+        impls.extend(unchecked.iter().map(|fields| {
+            let type_ = fields.ty();
+            quote_spanned! { fields.fields.span()=>
+                impl #impl_generics variant_access_traits::HasVariant<#type_> for #ident #ty_generics #where_clause {}
+            }
+        }));
+        let types_ = fresh_generic(params, "Types");
+        impls.extend(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Checks if the active field is of any of the types in the
+                /// given tuple, see `VariantSet`
+                #[allow(dead_code)]
+                pub fn contains_any<#types_: variant_access_traits::VariantSet<Self>>(&self) -> bool {
+                    #types_::any_active(self)
+                }
+            }
+        });
+    }
+    let TypedMethodGenerics {
+        type_,
+        generics,
+        marker,
+        bound,
+    } = TypedMethodGenerics::new(ast, params, types);
+    impls.extend(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Checks if the field of the given type is active, see `IsVariant`
+            #[allow(dead_code)]
+            pub fn contains #generics(&self) -> bool
+            where
+                Self: #bound variant_access_traits::IsVariant<#type_, #marker>,
+            {
+                variant_access_traits::IsVariant::<#type_, #marker>::is_variant(self)
+            }
+        }
+    });
+    impls
 }
 
 /// Implements the GetVariant trait that retrieves the
-/// tagged value of the requested type, if possible
+/// tagged value of the requested type, if possible. For fields
+/// stored on their own, GetVariantRef is also implemented to
+/// borrow the value. For variants with multiple fields, GetVariant
+/// is also implemented on references to the enum, returning a
+/// tuple of references to the fields.
 ///
 /// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool),
+///     F3(i64, bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let inner: &i64 = instance.get_variant_ref().unwrap(); // assigns &42 to inner_value
+/// // let inner: &bool = instance.get_variant_ref().unwrap() // panics because of unhandled Err.
+/// // let inner: &i32 = instance.get_variant_ref().unwrap() // will not compile as GetVariantRef<i32> is not implemented for Enum.
+/// // let inner: (&i64, &bool) = (&instance).get_variant().unwrap() // panics because of unhandled Err.
 /// ```
+/// Works similarly for get_variant_mut if instance is mutable; returns mutable references instead.
+fn impl_get_variant(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    // Implementations on references to the enum need an extra lifetime
+    let borrowed = borrowed_generics(&ast.generics);
+    let (borrowed_generics, _, _) = borrowed.split_for_impl();
+    let lifetime = syn::Lifetime::new("'variant_access", Span::call_site());
+    let ident = &ast.ident;
+    let mut tokens = TokenStream2::new();
+    for fields in types.iter() {
+        let type_ = fields.ty();
+        let error = access_error(ast, "wrong_active_field", &type_, quote! { &self });
+        let marker = fields.marker();
+        let field = fields.ident;
+        let pattern = fields.pattern();
+        let value = fields.value("");
+        let ref_value = fields.borrowed_value(false);
+        let mut_value = fields.borrowed_value(true);
+        tokens.extend(quote! {
+            impl #impl_generics variant_access_traits::GetVariant<#type_, #marker> for #ident #ty_generics #where_clause {
+                fn get_variant(self) -> Result<#type_, variant_access_traits::VariantAccessError<Self>> {
+                    match self {
+                        #ident::#field #pattern => Ok(#value),
+                        _ => Err(#error.with_inner(self))
+                    }
+                }
+            }
+        });
+        if fields.is_single() || fields.is_unit() {
+            tokens.extend(quote! {
+                impl #impl_generics variant_access_traits::GetVariantRef<#type_, #marker> for #ident #ty_generics #where_clause {
+                    fn get_variant_ref(&self) -> Result<&#type_, variant_access_traits::VariantAccessError> {
+                        match self {
+                            #ident::#field #pattern => Ok(#ref_value),
+                            _ => Err(#error)
+                        }
+                    }
+
+                    fn get_variant_mut(&mut self) -> Result<&mut #type_, variant_access_traits::VariantAccessError> {
+                        match self {
+                            #ident::#field #pattern => Ok(#mut_value),
+                            _ => Err(#error)
+                        }
+                    }
+                }
+            });
+        } else {
+            let ref_type = fields.borrowed_type(&lifetime, false);
+            let ref_error = access_error(ast, "wrong_active_field", &ref_type, quote! { &self });
+            let mut_type = fields.borrowed_type(&lifetime, true);
+            let mut_error = access_error(ast, "wrong_active_field", &mut_type, quote! { &self });
+            tokens.extend(quote! {
+                impl #borrowed_generics variant_access_traits::GetVariant<#ref_type, #marker> for &#lifetime #ident #ty_generics #where_clause {
+                    fn get_variant(self) -> Result<#ref_type, variant_access_traits::VariantAccessError<Self>> {
+                        match self {
+                            #ident::#field #pattern => Ok(#ref_value),
+                            _ => Err(#ref_error.with_inner(self))
+                        }
+                    }
+                }
+
+                impl #borrowed_generics variant_access_traits::GetVariant<#mut_type, #marker> for &#lifetime mut #ident #ty_generics #where_clause {
+                    fn get_variant(self) -> Result<#mut_type, variant_access_traits::VariantAccessError<Self>> {
+                        match self {
+                            #ident::#field #pattern => Ok(#mut_value),
+                            _ => Err(#mut_error.with_inner(self))
+                        }
+                    }
+                }
+            });
+        }
+    }
+    tokens
+}
+
+/// Implements VariantMarker for the type of each variant, if the marker
+/// of each type can be inferred.
+///
+/// # Example:
+/// ```ignore
 /// enum Enum {
 ///     F1(i64),
 ///     F2(bool)
 /// }
-/// let instance = Enum::f1(42);
+/// ```
+/// produces
+/// ```ignore
+/// impl VariantMarker<i64> for Enum {
+///     type Marker = variant_access_Enum::F1;
+/// }
 ///
-/// let inner: &i64 = instance.get_variant::<i64>().unwrap(); // assigns &42 to inner_value
-/// // let inner: &bool = instance.get_variant().unwrap() // panics because of unhandled Err.
-/// // let inner: &i32 = instance.get_variant().unwrap() // will not compile as GetVariant<i32> is not implemented for Enum.
+/// impl VariantMarker<bool> for Enum {
+///     type Marker = variant_access_Enum::F2;
+/// }
 /// ```
-/// Works similarly for get_variant_mut if instance is mutable; returns mutable references instead.
-fn impl_get_variant(
-    ast: &DeriveInput,
-    name: &str,
-    params: &[String],
-    types: &HashMap<String, &Ident>,
-    templater: &Tera,
-) -> TokenStream {
-    let mut impl_string = String::new();
-    // Determines if we are implementing the trait over generics
-    let generics = if !params.is_empty() {
-        format!("<{}>", ast.generics.params.to_token_stream())
-    } else {
-        String::from("")
-    };
-    for (type_, field_) in types.iter() {
-        let mut context = Context::new();
-        context.insert("generics", &generics);
-        context.insert("Type", &type_);
-        context.insert(
-            "Marker",
-            &format!(
-                "variant_access_{}::{}",
-                ast.ident.to_string(),
-                field_.to_string()
-            ),
-        );
-        context.insert("fullname", name);
-        context.insert("name", &ast.ident.to_string());
-        context.insert("field", &field_.to_string());
-        impl_string.push_str(
-            &templater
-                .render("get_variant", &context)
-                .expect("Failed to fill in GetVariant template"),
-        );
-    }
-
-    impl_string.parse().unwrap()
+/// except that the types checked by impl_distinct_types share one impl
+/// that takes the marker from the check.
+fn impl_variant_marker(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    if !markers_inferable(ast, types) {
+        return TokenStream2::new();
+    }
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let mut impls = TokenStream2::new();
+    // The checked types are covered by a single impl, so that identical
+    // types only conflict in the check for distinct types
+    let (checked, unchecked) = partition_distinct_types(ast, types);
+    if !checked.is_empty() {
+        let check = distinct_types_check(ast);
+        let type_ = fresh_generic(params, "Type");
+        let mut generics = ast.generics.clone();
+        generics.params.push(syn::parse_quote! { #type_: #check });
+        let (impl_generics, _, _) = generics.split_for_impl();
+        impls.extend(quote! {
+            impl #impl_generics variant_access_traits::VariantMarker<#type_> for #ident #ty_generics #where_clause {
+                type Marker = <#type_ as #check>::Marker;
+            }
+        });
+    }
+    impls.extend(unchecked.iter().map(|fields| {
+        let type_ = fields.ty();
+        let marker = fields.marker();
+        // point at the variant in case of conflicting implementations
+        quote_spanned! { fields.fields.span()=>
+            impl #impl_generics variant_access_traits::VariantMarker<#type_> for #ident #ty_generics #where_clause {
+                type Marker = #marker;
+            }
+        }
+    }));
+    impls
+}
+
+/// The generic parameters of inherent methods taking the type of a
+/// field, so that they can be called with turbofish syntax.
+struct TypedMethodGenerics {
+    /// The parameter for the type of the field
+    type_: Ident,
+    /// The generic parameters of the method
+    generics: TokenStream2,
+    /// The marker with which the traits are implemented for the type
+    marker: TokenStream2,
+    /// The bound on `Self` needed to name the marker, followed by `+`
+    bound: TokenStream2,
+}
+
+impl TypedMethodGenerics {
+    /// Where the marker of each type can be inferred, it is named
+    /// through VariantMarker, so that the type is the only generic
+    /// argument of the methods. Otherwise the marker is a second
+    /// generic argument.
+    fn new(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> Self {
+        let type_ = fresh_generic(params, "Type");
+        if markers_inferable(ast, types) {
+            TypedMethodGenerics {
+                generics: quote! { <#type_> },
+                marker: quote! { <Self as variant_access_traits::VariantMarker<#type_>>::Marker },
+                bound: quote! { variant_access_traits::VariantMarker<#type_> + },
+                type_,
+            }
+        } else {
+            let marker = fresh_generic(params, "Marker");
+            TypedMethodGenerics {
+                generics: quote! { <#type_, #marker> },
+                marker: marker.to_token_stream(),
+                bound: TokenStream2::new(),
+                type_,
+            }
+        }
+    }
+}
+
+/// Implements inherent methods borrowing the field of a given type,
+/// which unlike the methods of GetVariantRef can be called with
+/// turbofish syntax.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let inner: i64 = *instance.get::<i64>().unwrap() + 1; // assigns 43 to inner
+/// let inner: Option<&bool> = instance.try_get::<bool>(); // assigns None to inner
+/// let inner: bool = instance.get_or::<bool>(false); // assigns false to inner
+/// ```
+fn impl_get_methods(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let TypedMethodGenerics {
+        type_,
+        generics,
+        marker,
+        bound,
+    } = TypedMethodGenerics::new(ast, params, types);
+    let default = format_ident!("default");
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Borrows the field of the given type, see `GetVariantRef`
+            #[allow(dead_code)]
+            pub fn get #generics(&self) -> Result<&#type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_ref(self)
+            }
+
+            /// Mutably borrows the field of the given type, see `GetVariantRef`
+            #[allow(dead_code)]
+            pub fn get_mut #generics(&mut self) -> Result<&mut #type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_mut(self)
+            }
+
+            /// Borrows the field of the given type, or returns None if
+            /// another field is active
+            #[allow(dead_code)]
+            pub fn try_get #generics(&self) -> Option<&#type_>
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_ref(self).ok()
+            }
+
+            /// Copies the field of the given type
+            #[allow(dead_code)]
+            pub fn get_copied #generics(&self) -> Result<#type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+                #type_: Copy,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_ref(self).copied()
+            }
+
+            /// Clones the field of the given type
+            #[allow(dead_code)]
+            pub fn get_cloned #generics(&self) -> Result<#type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+                #type_: Clone,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_ref(self).cloned()
+            }
+
+            /// Clones the field of the given type, or returns the given
+            /// default if another field is active
+            #[allow(dead_code)]
+            pub fn get_or #generics(&self, #default: #type_) -> #type_
+            where
+                Self: #bound variant_access_traits::GetVariantRef<#type_, #marker>,
+                #type_: Clone,
+            {
+                variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_ref(self)
+                    .cloned()
+                    .unwrap_or(#default)
+            }
+        }
+    }
+}
+
+/// Implements inherent methods moving the field of a given type out
+/// of the enum, leaving another alternative in its place.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(String),
+///     F2(bool)
+/// }
+///
+/// impl Default for Enum {
+///     fn default() -> Self {
+///         Enum::F2(false)
+///     }
+/// }
+/// let mut instance = Enum::F1(String::from("42"));
+///
+/// let inner: String = instance.take_variant::<String>().unwrap(); // instance now is equal to Enum::F2(false)
+/// let inner = instance.take_variant_or::<bool>(Enum::F2(true)); // assigns Ok(false) to inner, instance is now equal to Enum::F2(true)
+/// let inner = instance.take_variant::<String>(); // assigns Err to inner, instance is unchanged
+/// ```
+fn impl_take_methods(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let TypedMethodGenerics {
+        type_,
+        generics,
+        marker,
+        bound,
+    } = TypedMethodGenerics::new(ast, params, types);
+    let error = access_error(ast, "wrong_active_field", &type_.to_token_stream(), quote! { self });
+    let replacement = format_ident!("replacement");
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Moves out the field of the given type, leaving the default
+            /// value of the enum in its place. If another field is active,
+            /// the enum is left unchanged.
+            #[allow(dead_code)]
+            pub fn take_variant #generics(&mut self) -> Result<#type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::IsVariant<#type_, #marker>
+                    + variant_access_traits::GetVariant<#type_, #marker>,
+                // A plain `Self: Default` fails for enums without Default; the lifetime defers it to calls
+                for<'variant_access> Self: Default,
+            {
+                if variant_access_traits::IsVariant::<#type_, #marker>::is_variant(self) {
+                    variant_access_traits::GetVariant::<#type_, #marker>::get_variant(std::mem::take(self))
+                        .map_err(variant_access_traits::VariantAccessError::without_inner)
+                } else {
+                    Err(#error)
+                }
+            }
+
+            /// Moves out the field of the given type, leaving the given
+            /// replacement in its place. If another field is active, the
+            /// enum is left unchanged.
+            #[allow(dead_code)]
+            pub fn take_variant_or #generics(&mut self, #replacement: Self) -> Result<#type_, variant_access_traits::VariantAccessError>
+            where
+                Self: #bound variant_access_traits::IsVariant<#type_, #marker>
+                    + variant_access_traits::GetVariant<#type_, #marker>,
+            {
+                if variant_access_traits::IsVariant::<#type_, #marker>::is_variant(self) {
+                    variant_access_traits::GetVariant::<#type_, #marker>::get_variant(std::mem::replace(self, #replacement))
+                        .map_err(variant_access_traits::VariantAccessError::without_inner)
+                } else {
+                    Err(#error)
+                }
+            }
+        }
+    }
+}
+
+/// Implements an inherent method setting the field of a given type
+/// to the value returned by a closure, much like `emplace` of
+/// `std::variant`.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(Vec<bool>)
+/// }
+/// let mut instance = Enum::F1(42);
+///
+/// instance.emplace_with::<Vec<bool>>(Vec::new).push(true); // instance now is equal to Enum::F2(vec![true])
+/// ```
+fn impl_emplace_with(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let TypedMethodGenerics {
+        type_,
+        generics,
+        marker,
+        bound,
+    } = TypedMethodGenerics::new(ast, params, types);
+    let construct = format_ident!("construct");
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Sets the field of the given type to the value returned by
+            /// the closure, returning a mutable reference to it.
+            #[allow(dead_code)]
+            pub fn emplace_with #generics(&mut self, #construct: impl FnOnce() -> #type_) -> &mut #type_
+            where
+                Self: #bound variant_access_traits::SetVariant<#type_, #marker>
+                    + variant_access_traits::GetVariantRef<#type_, #marker>,
+            {
+                variant_access_traits::SetVariant::<#type_, #marker>::set_variant(self, #construct());
+                match variant_access_traits::GetVariantRef::<#type_, #marker>::get_variant_mut(self) {
+                    Ok(value) => value,
+                    Err(_) => unreachable!(),
+                }
+            }
+        }
+    }
 }
 
 /// Implements the SetVariant trait that sets the
 /// tagged value of the field whose type matches the input value, if possible
 ///
 /// # Example:
-/// ```
+/// ```ignore
 /// enum Enum {
 ///     F1(i64),
 ///     F2(bool)
@@ -163,7 +1076,7 @@ fn impl_get_variant(
 /// be ambiguuous sometimes.
 ///
 /// # Example:
-/// ```
+/// ```ignore
 /// enum Enum {
 ///     F1(i32),
 ///     F2(i64)
@@ -176,47 +1089,32 @@ fn impl_get_variant(
 /// instance.set_variant(1 as i32); // instance equals Enum::F1(1)
 /// instance.set_variant(1 as i64); // instance equal Enum::F2(1)
 /// ```
-fn impl_set_variant(
-    ast: &DeriveInput,
-    name: &str,
-    params: &[String],
-    types: &HashMap<String, &Ident>,
-    templater: &Tera,
-) -> TokenStream {
-    let mut impl_string = String::new();
-    let generics = if !params.is_empty() {
-        format!("<{}>", ast.generics.params.to_token_stream())
-    } else {
-        String::from("")
-    };
-    for (type_, field_) in types.iter() {
-        let mut context = Context::new();
-        context.insert("generics", &generics);
-        context.insert("Type", &type_);
-        context.insert(
-            "Marker",
-            &format!(
-                "variant_access_{}::{}",
-                ast.ident.to_string(),
-                field_.to_string()
-            ),
-        );
-        context.insert("fullname", name);
-        context.insert("name", &ast.ident.to_string());
-        context.insert("field", &field_.to_string());
-        impl_string.push_str(
-            &templater
-                .render("set_variant", &context)
-                .expect("Failed to fill in GetVariant template"),
-        );
-    }
-    impl_string.parse().unwrap()
+fn impl_set_variant(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    types
+        .iter()
+        .map(|fields| {
+            let type_ = fields.ty();
+            let marker = fields.marker();
+            let field = fields.ident;
+            let construct = fields.construct(&value);
+            quote! {
+                impl #impl_generics variant_access_traits::SetVariant<#type_, #marker> for #ident #ty_generics #where_clause {
+                    fn set_variant(&mut self, #value: #type_) {
+                        *self = #ident::#field #construct;
+                    }
+                }
+            }
+        })
+        .collect()
 }
 
 /// This trait allows one to create a new instance of an enum from a value whose type matches one
 /// of the types of the field of the enum.
 /// # Example:
-///```
+///```ignore
 /// use variant_access_traits::*;
 /// use variant_access_derive::*;
 ///
@@ -232,63 +1130,481 @@ fn impl_set_variant(
 ///
 /// This trait has a generic paramer `Marker` for adding marker structs. This is used if implementing
 /// this trait for enums with more than one generic parameter in order to avoid definition clashes.
-fn impl_create_variant(
-    ast: &DeriveInput,
-    name: &str,
-    params: &[String],
-    types: &HashMap<String, &Ident>,
-    templater: &Tera,
-) -> TokenStream {
-    let mut impl_string = String::new();
-    let generics = if !params.is_empty() {
-        format!("<{}>", &ast.generics.params.to_token_stream())
+fn impl_create_variant(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    types
+        .iter()
+        .map(|fields| {
+            let type_ = fields.ty();
+            let marker = fields.marker();
+            let field = fields.ident;
+            let construct = fields.construct(&value);
+            quote! {
+                impl #impl_generics variant_access_traits::CreateVariantFrom<#type_, #marker> for #ident #ty_generics #where_clause {
+                    fn create_variant_from(#value: #type_) -> Self {
+                        #ident::#field #construct
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// The variants that can be converted to and from the enum with From
+/// and TryFrom. Where the types of two fields could be the same type,
+/// these conversions would conflict, so only the fields whose types
+/// do not mention the generic parameters of the enum are converted.
+fn convertible_fields<'a, 'b>(ast: &DeriveInput, types: &'b [VariantFields<'a>]) -> Vec<&'b VariantFields<'a>> {
+    if markers_inferable(ast, types) {
+        types.iter().collect()
     } else {
-        String::from("")
+        types
+            .iter()
+            .filter(|fields| !mentions_generics(ast, fields.ty()))
+            .collect()
+    }
+}
+
+/// Implements From for the type of each field, if requested with
+/// `#[variant_access(from)]`
+///
+/// # Example:
+/// ```ignore
+/// #[variant_access(from)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// let instance: Enum = false.into(); // instance is now equal to Enum::F2(false)
+/// ```
+fn impl_from(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    convertible_fields(ast, types)
+        .into_iter()
+        .map(|fields| {
+            let type_ = fields.ty();
+            let field = fields.ident;
+            let construct = fields.construct(&value);
+            quote! {
+                impl #impl_generics std::convert::From<#type_> for #ident #ty_generics #where_clause {
+                    fn from(#value: #type_) -> Self {
+                        #ident::#field #construct
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Implements TryFrom the enum for the type of each field, if requested
+/// with `#[variant_access(try_into)]`. The error gives back the enum.
+///
+/// # Example:
+/// ```ignore
+/// #[variant_access(try_into)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// let inner: Result<i64, _> = Enum::F1(42).try_into(); // assigns Ok(42) to inner
+/// let inner: Result<bool, _> = Enum::F1(42).try_into(); // assigns Err to inner, from which Enum::F1(42) can be recovered
+/// ```
+fn impl_try_from(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let value = format_ident!("value");
+    convertible_fields(ast, types)
+        .into_iter()
+        .map(|fields| {
+            let type_ = fields.ty();
+            let field = fields.ident;
+            let pattern = fields.pattern();
+            let inner = fields.value("");
+            let error = access_error(ast, "wrong_active_field", &type_, quote! { &#value });
+            quote! {
+                impl #impl_generics std::convert::TryFrom<#ident #ty_generics> for #type_ #where_clause {
+                    type Error = variant_access_traits::VariantAccessError<#ident #ty_generics>;
+
+                    fn try_from(#value: #ident #ty_generics) -> Result<Self, Self::Error> {
+                        match #value {
+                            #ident::#field #pattern => Ok(#inner),
+                            _ => Err(#error.with_inner(#value))
+                        }
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Implements the VariantAt trait for the alternative at each index,
+/// along with VariantAtRef for those stored as a single field.
+/// Inherent methods taking the index as a const generic argument are
+/// implemented as well, as the methods of the traits cannot be called
+/// with turbofish syntax.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i32),
+///     F2(i64)
+/// }
+///
+/// let mut instance = Enum::create_at::<0>(42); // instance is now equal to Enum::F1(42)
+/// instance.set_at::<1>(1); // instance now equals Enum::F2(1), without annotating the literal
+/// let inner: &i64 = instance.get_at::<1>().unwrap(); // assigns &1 to inner
+/// ```
+fn impl_variant_at(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let index = fresh_generic(params, "INDEX");
+    let value = format_ident!("value");
+    let mut tokens = TokenStream2::new();
+    for (position, fields) in types.iter().enumerate() {
+        let type_ = fields.ty();
+        let error = access_error(ast, "wrong_active_field", &type_, quote! { self });
+        let field = fields.ident;
+        let pattern = fields.pattern();
+        let construct = fields.construct(&value);
+        let ref_value = fields.borrowed_value(false);
+        let mut_value = fields.borrowed_value(true);
+        tokens.extend(quote! {
+            impl #impl_generics variant_access_traits::VariantAt<#position> for #ident #ty_generics #where_clause {
+                type Type = #type_;
+
+                fn set_at(&mut self, #value: #type_) {
+                    *self = #ident::#field #construct;
+                }
+
+                fn create_at(#value: #type_) -> Self {
+                    #ident::#field #construct
+                }
+            }
+        });
+        if fields.is_single() || fields.is_unit() {
+            tokens.extend(quote! {
+                impl #impl_generics variant_access_traits::VariantAtRef<#position> for #ident #ty_generics #where_clause {
+                    fn get_at(&self) -> Result<&#type_, variant_access_traits::VariantAccessError> {
+                        match self {
+                            #ident::#field #pattern => Ok(#ref_value),
+                            _ => Err(#error)
+                        }
+                    }
+
+                    fn get_at_mut(&mut self) -> Result<&mut #type_, variant_access_traits::VariantAccessError> {
+                        match self {
+                            #ident::#field #pattern => Ok(#mut_value),
+                            _ => Err(#error)
+                        }
+                    }
+                }
+            });
+        }
+    }
+    tokens.extend(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Borrows the alternative at the given index, see `VariantAtRef`
+            #[allow(dead_code)]
+            pub fn get_at<const #index: usize>(
+                &self,
+            ) -> Result<&<Self as variant_access_traits::VariantAt<#index>>::Type, variant_access_traits::VariantAccessError>
+            where
+                Self: variant_access_traits::VariantAtRef<#index>,
+            {
+                variant_access_traits::VariantAtRef::<#index>::get_at(self)
+            }
+
+            /// Mutably borrows the alternative at the given index, see `VariantAtRef`
+            #[allow(dead_code)]
+            pub fn get_at_mut<const #index: usize>(
+                &mut self,
+            ) -> Result<&mut <Self as variant_access_traits::VariantAt<#index>>::Type, variant_access_traits::VariantAccessError>
+            where
+                Self: variant_access_traits::VariantAtRef<#index>,
+            {
+                variant_access_traits::VariantAtRef::<#index>::get_at_mut(self)
+            }
+
+            /// Sets the alternative at the given index, see `VariantAt`
+            #[allow(dead_code)]
+            pub fn set_at<const #index: usize>(&mut self, #value: <Self as variant_access_traits::VariantAt<#index>>::Type)
+            where
+                Self: variant_access_traits::VariantAt<#index>,
+            {
+                variant_access_traits::VariantAt::<#index>::set_at(self, #value)
+            }
+
+            /// Creates the alternative at the given index, see `VariantAt`
+            #[allow(dead_code)]
+            pub fn create_at<const #index: usize>(#value: <Self as variant_access_traits::VariantAt<#index>>::Type) -> Self
+            where
+                Self: variant_access_traits::VariantAt<#index>,
+            {
+                <Self as variant_access_traits::VariantAt<#index>>::create_at(#value)
+            }
+        }
+    });
+    tokens
+}
+
+/// Implements the Visit, VisitMut and IntoVisit traits passing the
+/// active field to a visitor. Each requires the visitor to handle
+/// the type of every field, borrowed accordingly.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// ```
+/// produces
+/// ```ignore
+/// impl<V> Visit<V> for Enum
+/// where
+///     V: for<'variant_access> VisitType<&'variant_access i64>
+///         + for<'variant_access> VisitType<&'variant_access bool>,
+/// {
+///     fn visit(&self, visitor: V) -> V::Output {
+///         match self {
+///             Enum::F1(inner) => visitor.visit_type(inner),
+///             Enum::F2(inner) => visitor.visit_type(inner),
+///         }
+///     }
+/// }
+/// ```
+/// along with similar impls of VisitMut and IntoVisit.
+fn impl_visit(ast: &DeriveInput, params: &[String], types: &[VariantFields<'_>]) -> TokenStream2 {
+    let ident = &ast.ident;
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    // As the field types are named in the bounds of the visitor, it is
+    // also prefixed so as not to shadow them
+    let visitor = format_ident!("__{}", fresh_generic(params, "V"));
+    let lifetime = syn::Lifetime::new("'variant_access", Span::call_site());
+    let fields: Vec<&Ident> = types.iter().map(|fields| fields.ident).collect();
+    let patterns: Vec<TokenStream2> = types.iter().map(VariantFields::pattern).collect();
+
+    // The bound on the visitor and the visited value for each variant,
+    // depending on how the enum is accessed
+    let visited = |mutable: Option<bool>| -> (Vec<TokenStream2>, Vec<TokenStream2>) {
+        types
+            .iter()
+            .map(|fields| match mutable {
+                None => {
+                    let type_ = fields.ty();
+                    (quote! { variant_access_traits::VisitType<#type_> }, fields.value(""))
+                }
+                Some(mutable) => {
+                    let type_ = if fields.is_single() || fields.is_unit() {
+                        let mutability = if mutable { quote! { mut } } else { quote! {} };
+                        let type_ = fields.ty();
+                        quote! { &#lifetime #mutability #type_ }
+                    } else {
+                        fields.borrowed_type(&lifetime, mutable)
+                    };
+                    let bound = quote! { for<#lifetime> variant_access_traits::VisitType<#type_> };
+                    (bound, fields.borrowed_value(mutable))
+                }
+            })
+            .unzip()
     };
-    for (type_, field_) in types.iter() {
-        let mut context = Context::new();
-        context.insert("generics", &generics);
-        context.insert("Type", &type_);
-        context.insert(
-            "Marker",
-            &format!(
-                "variant_access_{}::{}",
-                ast.ident.to_string(),
-                field_.to_string()
-            ),
-        );
-        context.insert("fullname", name);
-        context.insert("name", &ast.ident.to_string());
-        context.insert("field", &field_.to_string());
-        impl_string.push_str(
-            &templater
-                .render("create_variant", &context)
-                .expect("Failed to fill in CreateVariantFrom template"),
-        );
-    }
-    impl_string.parse().unwrap()
-}
-
-/// Implements ContainsVariant, GetVariant, SetVariant, and CreateVariantFrom traits
-fn impl_variant_access(ast: &DeriveInput) -> TokenStream {
-    let mut tera = Tera::new("/dev/null/*").unwrap();
-    tera.add_raw_template("contains_variant", CONTAINS_VARIANT_TEMPLATE)
-        .unwrap();
-    tera.add_raw_template("get_variant", GET_VARIANT_TEMPLATE)
-        .unwrap();
-    tera.add_raw_template("set_variant", SET_VARIANT_TEMPLATE)
-        .unwrap();
-    tera.add_raw_template("create_variant", CREATE_VARIANT_TEMPLATE)
-        .unwrap();
-    let mut tokens: TokenStream = "".parse().unwrap();
-
-    let (name, params) = fetch_name_with_generic_params(&ast);
-    let types = fetch_types_from_enum(&ast);
-
-    tokens.extend::<TokenStream>(create_marker_structs(&ast.ident.to_string(), &types));
-    tokens.extend::<TokenStream>(impl_contains_variant(&ast, &name, &params, &types, &tera));
-    tokens.extend::<TokenStream>(impl_get_variant(&ast, &name, &params, &types, &tera));
-    tokens.extend::<TokenStream>(impl_set_variant(&ast, &name, &params, &types, &tera));
-    tokens.extend::<TokenStream>(impl_create_variant(&ast, &name, &params, &types, &tera));
+    let generics_with = |bounds: &[TokenStream2]| -> syn::Generics {
+        let mut generics = ast.generics.clone();
+        generics.params.push(syn::parse_quote! { #visitor });
+        let where_clause = generics.make_where_clause();
+        // Without variants, nothing else would require this
+        where_clause
+            .predicates
+            .push(syn::parse_quote! { #visitor: variant_access_traits::Visitor });
+        for bound in bounds {
+            where_clause.predicates.push(syn::parse_quote! { #visitor: #bound });
+        }
+        generics
+    };
+
+    let mut tokens = TokenStream2::new();
+    for (trait_, method, receiver, mutable) in [
+        (quote! { Visit }, quote! { visit }, quote! { &self }, Some(false)),
+        (quote! { VisitMut }, quote! { visit_mut }, quote! { &mut self }, Some(true)),
+        (quote! { IntoVisit }, quote! { into_visit }, quote! { self }, None),
+    ]
+    .iter()
+    {
+        let (bounds, values) = visited(*mutable);
+        let generics = generics_with(&bounds);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        // A reference to an enum without variants is only known to be
+        // uninhabited once dereferenced, which is only allowed without arms
+        let scrutinee = if types.is_empty() && mutable.is_some() {
+            quote! { *self }
+        } else {
+            quote! { self }
+        };
+        tokens.extend(quote! {
+            impl #impl_generics variant_access_traits::#trait_<#visitor> for #ident #ty_generics #where_clause {
+                fn #method(#receiver, visitor: #visitor) -> <#visitor as variant_access_traits::Visitor>::Output {
+                    match #scrutinee {
+                        #(#ident::#fields #patterns => variant_access_traits::VisitType::visit_type(visitor, #values),)*
+                    }
+                }
+            }
+        });
+    }
     tokens
 }
+
+/// Implements the Alternatives trait listing the types of the
+/// alternatives as a nested tuple, in the order they are declared.
+///
+/// # Example:
+/// ```ignore
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// ```
+/// produces
+/// ```ignore
+/// impl Alternatives for Enum {
+///     type List = (i64, (bool, ()));
+/// }
+/// ```
+fn impl_alternatives(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let list = types
+        .iter()
+        .rev()
+        .fold(quote! { () }, |list, fields| {
+            let type_ = fields.ty();
+            quote! { (#type_, #list) }
+        });
+    quote! {
+        impl #impl_generics variant_access_traits::Alternatives for #ident #ty_generics #where_clause {
+            type List = #list;
+        }
+    }
+}
+
+/// Implements ContainsVariant, ActiveVariant, VariantTypes, IsVariant, GetVariant, SetVariant, CreateVariantFrom, VariantAt, Visit and Alternatives traits,
+/// or those of them listed in `#[variant_access(derive(...))]` on the enum
+fn impl_variant_access(ast: &DeriveInput) -> TokenStream {
+    let (_, params) = fetch_name_with_generic_params(ast);
+    let (options, types) = match (fetch_enum_options(ast), fetch_types_from_enum(ast)) {
+        (Ok(options), Ok(types)) => (options, types),
+        (Err(mut error), Err(other)) => {
+            error.combine(other);
+            return error.to_compile_error().into();
+        }
+        (Err(error), _) | (_, Err(error)) => return error.to_compile_error().into(),
+    };
+
+    let markers = create_marker_structs(ast, &types);
+    let mut impls = impl_distinct_types(ast, &types);
+    impls.extend(define_active_type_name(ast, &types));
+    // TypeId is only available for 'static types
+    if has_lifetime_params(ast) {
+        for trait_ in ["ContainsVariant", "ActiveVariant", "VariantTypes"].iter() {
+            if let Some(requested) = options.requested(trait_) {
+                return syn::Error::new_spanned(
+                    requested,
+                    format!(
+                        "{} relies on `std::any::TypeId` and cannot be derived \
+                         for enums with lifetime parameters",
+                        trait_
+                    ),
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+    } else {
+        if options.derives("ContainsVariant") {
+            impls.extend(impl_contains_variant(ast, &params, &types));
+            impls.extend(impl_any_methods(ast, &types));
+        }
+        if options.derives("ActiveVariant") {
+            impls.extend(impl_active_variant(ast, &types));
+        }
+        if options.derives("VariantTypes") {
+            impls.extend(impl_variant_types(ast, &types));
+        }
+        // Downcasting relies on TypeId as well
+        if options.derives("SetVariant") {
+            impls.extend(impl_set_from_any(ast, &types));
+        }
+        if options.derives("CreateVariantFrom") {
+            impls.extend(impl_try_from_any(ast, &types));
+        }
+    }
+    if options.derives("IsVariant") {
+        impls.extend(impl_is_variant(ast, &types));
+        impls.extend(impl_has_variant(ast, &params, &types));
+    }
+    if options.derives("GetVariant") {
+        impls.extend(impl_get_variant(ast, &types));
+        impls.extend(impl_get_methods(ast, &params, &types));
+        impls.extend(impl_take_methods(ast, &params, &types));
+    }
+    if options.derives("SetVariant") {
+        impls.extend(impl_set_variant(ast, &types));
+        impls.extend(impl_emplace_with(ast, &params, &types));
+    }
+    if options.derives("CreateVariantFrom") {
+        impls.extend(impl_create_variant(ast, &types));
+    }
+    if ["IsVariant", "GetVariant", "SetVariant", "CreateVariantFrom"]
+        .iter()
+        .any(|trait_| options.derives(trait_))
+    {
+        impls.extend(impl_variant_marker(ast, &params, &types));
+    }
+    if options.from {
+        impls.extend(impl_from(ast, &types));
+    }
+    if options.try_into {
+        impls.extend(impl_try_from(ast, &types));
+    }
+    if options.derives("VariantAt") {
+        impls.extend(impl_variant_at(ast, &params, &types));
+    }
+    // Skipped variants can neither be visited nor listed as alternatives
+    match fetch_skipped_variant(ast) {
+        Some(skipped) => {
+            for trait_ in ["Visit", "Alternatives"].iter() {
+                if let Some(requested) = options.requested(trait_) {
+                    let mut error = syn::Error::new_spanned(
+                        requested,
+                        format!("{} cannot be derived for enums with skipped variants", trait_),
+                    );
+                    error.combine(syn::Error::new_spanned(skipped, "variant skipped here"));
+                    return error.to_compile_error().into();
+                }
+            }
+        }
+        None => {
+            if options.derives("Visit") {
+                impls.extend(impl_visit(ast, &params, &types));
+            }
+            if options.derives("Alternatives") {
+                impls.extend(impl_alternatives(ast, &types));
+            }
+        }
+    }
+    // The field types keep their spans, so lints on them would be
+    // reported for the enum even where they are silenced on it.
+    let tokens = quote! {
+        #markers
+        #[allow(unused_parens)]
+        const _: () = {
+            #impls
+        };
+    };
+    tokens.into()
+}
@@ -0,0 +1,141 @@
+use super::*;
+use syn::parse::{Parse, ParseStream};
+
+/// The input of `match_variant!`: the matched value, followed by
+/// arms of the form `pattern: Type => body` and optionally a final
+/// `_ => body` arm.
+pub struct MatchVariant {
+    value: syn::Expr,
+    arms: Vec<TypeArm>,
+    default: Option<(syn::Token![_], syn::Expr)>,
+}
+
+/// An arm of `match_variant!` handling the alternative of the given type
+struct TypeArm {
+    pattern: syn::Pat,
+    ty: syn::Type,
+    body: syn::Expr,
+}
+
+impl Parse for MatchVariant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Like the scrutinee of `match`, the value cannot be a struct literal
+        let value = syn::Expr::parse_without_eager_brace(input)?;
+        let content;
+        syn::braced!(content in input);
+        let mut arms = vec![];
+        let mut default = None;
+        while !content.is_empty() {
+            if default.is_some() {
+                return Err(content.error("the `_` arm must be the last arm of `match_variant!`"));
+            }
+            let pattern: syn::Pat = content.parse()?;
+            match pattern {
+                syn::Pat::Wild(wild) if content.peek(syn::Token![=>]) => {
+                    content.parse::<syn::Token![=>]>()?;
+                    default = Some((wild.underscore_token, parse_body(&content)?));
+                }
+                pattern => {
+                    content.parse::<syn::Token![:]>()?;
+                    let ty: syn::Type = content.parse()?;
+                    content.parse::<syn::Token![=>]>()?;
+                    let body = parse_body(&content)?;
+                    arms.push(TypeArm { pattern, ty, body });
+                }
+            }
+        }
+        Ok(MatchVariant { value, arms, default })
+    }
+}
+
+/// Parses the body of an arm along with the comma following it,
+/// which is optional after blocks and at the end, as for `match`.
+fn parse_body(input: ParseStream) -> syn::Result<syn::Expr> {
+    let body: syn::Expr = input.parse()?;
+    let block_like = matches!(
+        body,
+        syn::Expr::Block(_)
+            | syn::Expr::If(_)
+            | syn::Expr::Match(_)
+            | syn::Expr::Loop(_)
+            | syn::Expr::While(_)
+            | syn::Expr::ForLoop(_)
+            | syn::Expr::Unsafe(_)
+    );
+    if input.peek(syn::Token![,]) {
+        input.parse::<syn::Token![,]>()?;
+    } else if !block_like && !input.is_empty() {
+        return Err(input.error("expected `,` following the body of the arm"));
+    }
+    Ok(body)
+}
+
+/// Expands `match_variant!` into a chain of checks of the active
+/// field, each extracting the value of the field in its branch. The
+/// matched value is moved into the branch that is taken.
+///
+/// Unless there is a `_` arm, it is checked at compile time that the
+/// arms handle exactly the alternatives listed by the Alternatives
+/// trait of the enum.
+///
+/// # Example
+/// ```ignore
+/// match_variant!(value {
+///     x: i64 => x + 1,
+///     b: bool => b as i64,
+/// })
+/// ```
+/// expands to
+/// ```ignore
+/// {
+///     let value = value;
+///     check_exhaustive::<_, (i64, (bool, ())), _>(&value);
+///     if IsVariant::<i64, _>::is_variant(&value) {
+///         let x: i64 = GetVariant::<i64, _>::get_variant(value).unwrap();
+///         x + 1
+///     } else if IsVariant::<bool, _>::is_variant(&value) {
+///         let b: bool = GetVariant::<bool, _>::get_variant(value).unwrap();
+///         b as i64
+///     } else {
+///         unreachable!()
+///     }
+/// }
+/// ```
+pub fn expand_match_variant(input: MatchVariant) -> TokenStream2 {
+    let MatchVariant { value, arms, default } = input;
+    // The matched value is not visible to the bodies of the arms
+    let matched = Ident::new("value", Span::mixed_site());
+    let check = match &default {
+        Some(_) => TokenStream2::new(),
+        None => {
+            let list = arms.iter().rev().fold(quote! { () }, |list, arm| {
+                let ty = &arm.ty;
+                quote! { (#ty, #list) }
+            });
+            quote! { variant_access_traits::check_exhaustive::<_, #list, _>(&#matched); }
+        }
+    };
+    let branches = arms.iter().map(|arm| {
+        let TypeArm { pattern, ty, body } = arm;
+        quote_spanned! { ty.span()=>
+            if variant_access_traits::IsVariant::<#ty, _>::is_variant(&#matched) {
+                let #pattern: #ty = match variant_access_traits::GetVariant::<#ty, _>::get_variant(#matched) {
+                    Ok(inner) => inner,
+                    Err(_) => unreachable!(),
+                };
+                #body
+            } else
+        }
+    });
+    let fallback = match default {
+        Some((_, body)) => quote! { { #body } },
+        None => quote! { { unreachable!() } },
+    };
+    quote! {
+        {
+            let #matched = #value;
+            #check
+            #(#branches)* #fallback
+        }
+    }
+}
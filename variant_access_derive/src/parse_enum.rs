@@ -1,80 +1,94 @@
 use super::*;
 
 /// If the decorated enum has generic template parameters,
-/// we determine those here.We also validate that there
-/// are no lifetime parameters. If there are, the macro
-/// panics.
+/// we determine those here.
 ///
-/// Otherwise, the full declaration of the type and a map
-/// from each generic parameter to it's trait bounds is
-/// returned.
+/// The full declaration of the type and the names of its
+/// generic parameters are returned.
 ///
 /// # Example
-/// ```
+/// ```ignore
 /// #[derive(VariantAccess)]
-/// enum Enum<T: 'static + Debug, X: 'static> {
-///     F1(T),
-///     F2(X)
+/// enum Enum<'a, T: 'static + Debug, X: 'static, const N: usize> {
+///     F1(&'a T),
+///     F2(X),
+///     F3([u8; N])
 /// }
 /// ```
-/// This function then returns `(Enum<T, X>, vec!["T", "X"])`
-///
-/// # Example
-/// ```
-/// #[derive(VariantAccess)]
-/// enum Enum<'a, T: 'a, X> {
-///     F1(T),
-///     F2(X)
-/// }
-/// ```
-/// This function panics on the above example.
+/// This function then returns `(Enum<'a,T,X,N>, vec!["'a", "T", "X", "N"])`
 pub fn fetch_name_with_generic_params(ast: &DeriveInput) -> (String, Vec<String>) {
-    let mut param_string = String::from("");
     let params: Vec<String> = ast
         .generics
         .params
         .iter()
-        .filter_map(|param| match param {
-            GenericParam::Lifetime(inner) => {
-                if inner.lifetime.ident.to_token_stream().to_string() != String::from("static") {
-                    panic!("VariantAccess can only be derived for types with static lifetimes");
-                } else {
-                    None
-                }
-            }
-            GenericParam::Type(inner) => {
-                param_string.push_str(&format!("{},", inner.ident.to_string()));
-                Some(inner.ident.to_string())
-            }
-            GenericParam::Const(_) => {
-                panic!("VariantAccess does not currently support const generics")
-            }
+        .map(|param| match param {
+            GenericParam::Lifetime(inner) => inner.lifetime.to_string(),
+            GenericParam::Type(inner) => inner.ident.to_string(),
+            GenericParam::Const(inner) => inner.ident.to_string(),
         })
         .collect();
-    param_string.pop();
     if !params.is_empty() {
-        (format!("{}<{}>", ast.ident, param_string), params)
+        (format!("{}<{}>", ast.ident, params.join(",")), params)
     } else {
         (ast.ident.to_string(), params)
     }
 }
 
+/// Adds the lifetime `'variant_access` in front of the given generics,
+/// as needed by impls on references to the enum and by the generated
+/// structs holding references to the fields of a variant.
+pub fn borrowed_generics(generics: &syn::Generics) -> syn::Generics {
+    let mut generics = generics.clone();
+    let lifetime = syn::Lifetime::new("'variant_access", Span::call_site());
+    generics
+        .params
+        .insert(0, GenericParam::Lifetime(syn::LifetimeDef::new(lifetime)));
+    generics
+}
+
+/// Whether the decorated enum has lifetime parameters. As
+/// `std::any::TypeId` is only available for `'static` types,
+/// ContainsVariant is not implemented for such enums and
+/// IsVariant should be used instead.
+pub fn has_lifetime_params(ast: &DeriveInput) -> bool {
+    ast.generics.lifetimes().next().is_some()
+}
+
 /// Recursively parse a type to construct the string representation
 /// of it. This includes fully qualifying namespaces as necessary.
 ///
-/// Not all types are supported as fields in the enum. Several of
-/// these are due the fact that they are not 'static. It would
-/// be nice support impl Traits.
-fn parse_type(ty: &syn::Type) -> String {
-    match ty {
-        syn::Type::Array(array) => parse_array(array),
-        syn::Type::Tuple(tuple) => parse_tuple(tuple),
-        syn::Type::Path(path) => parse_path(path),
-        other @ _ => panic!(
-            "VariantAccess cannot be derived for enums with a field of type: {:?}",
-            other.to_token_stream()
-        ),
-    }
+/// Parentheses and the invisible groups around types substituted
+/// by `macro_rules!` are dropped, so that e.g. `(i64)` and `i64` are
+/// the same type. Types without nested types to parse, such as
+/// function pointers and trait objects, are named as written.
+///
+/// `impl Trait` and `_` cannot appear in the fields of an enum,
+/// and as such an error pointing at them is returned.
+fn parse_type(ty: &syn::Type) -> syn::Result<String> {
+    Ok(match ty {
+        syn::Type::Array(array) => parse_array(array)?,
+        syn::Type::Slice(slice) => format!("[{}]", parse_type(&slice.elem)?),
+        syn::Type::Tuple(tuple) => parse_tuple(tuple)?,
+        syn::Type::Path(path) => parse_path(path)?,
+        syn::Type::Reference(reference) => parse_reference(reference)?,
+        syn::Type::Ptr(ptr) => parse_ptr(ptr)?,
+        syn::Type::Paren(paren) => parse_type(&paren.elem)?,
+        syn::Type::Group(group) => parse_type(&group.elem)?,
+        syn::Type::Never(_) => String::from("!"),
+        syn::Type::BareFn(_) | syn::Type::TraitObject(_) | syn::Type::Macro(_) => {
+            remove_whitespace(&ty.to_token_stream().to_string())
+        }
+        other => {
+            return Err(syn::Error::new_spanned(
+                other,
+                format!(
+                    "VariantAccess cannot be derived for enums with a field of type `{}`; \
+                     use a concrete type or a generic parameter instead",
+                    other.to_token_stream()
+                ),
+            ))
+        }
+    })
 }
 
 /// Determines the full path of a named type including all of its nested namespaces.
@@ -82,7 +96,7 @@ fn parse_type(ty: &syn::Type) -> String {
 /// namespace resolution.
 ///
 /// # Exmample
-/// ```
+/// ```ignore
 /// pub struct Complex {
 ///     field_one: bool,
 ///     field_two: f64
@@ -105,78 +119,594 @@ fn parse_type(ty: &syn::Type) -> String {
 ///```
 /// The path name of the type of `F1` is `namespace::Complex<T>` and the path name of the type of
 /// `F2` is `Complex`.
-fn parse_path(path: &syn::TypePath) -> String {
+///
+/// Qualified paths such as `<T as Trait>::Output` keep their
+/// qualified self type.
+fn parse_path(path: &syn::TypePath) -> syn::Result<String> {
     let mut fullname = String::from("");
-    let _ = path
-        .path
-        .segments
-        .pairs()
-        .map(|segment| fullname.push_str(&segment.to_token_stream().to_string()))
-        .collect::<()>();
-
-    // remove extraneous whitespace
-    fullname.retain(|c| c != ' ');
+    if let Some(qself) = &path.qself {
+        // the segments after `as` belong to the trait
+        let trait_path: Vec<String> = path
+            .path
+            .segments
+            .iter()
+            .take(qself.position)
+            .map(|segment| segment.to_token_stream().to_string())
+            .collect();
+        let leading_colon = if path.path.leading_colon.is_some() { "::" } else { "" };
+        if trait_path.is_empty() {
+            fullname.push_str(&format!("<{}>::", parse_type(&qself.ty)?));
+        } else {
+            fullname.push_str(&format!(
+                "<{} as {}{}>::",
+                parse_type(&qself.ty)?,
+                leading_colon,
+                trait_path.join("::")
+            ));
+        }
+        for segment in path.path.segments.iter().skip(qself.position) {
+            fullname.push_str(&segment.to_token_stream().to_string());
+            fullname.push_str("::");
+        }
+        fullname.truncate(fullname.len() - 2);
+        return Ok(remove_whitespace(&fullname));
+    }
+    if path.path.leading_colon.is_some() {
+        fullname.push_str("::");
+    }
+    for segment in path.path.segments.pairs() {
+        fullname.push_str(&segment.to_token_stream().to_string());
+    }
+
+    Ok(remove_whitespace(&fullname))
+}
+
+/// Removes extraneous whitespace from the name of a type. Whitespace
+/// separating two words, e.g. in `&'a mut T` or `dyn Trait`, is kept.
+fn remove_whitespace(name: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut fullname = String::new();
+    let mut pending_space = false;
+    for c in name.chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && is_word(c) && fullname.chars().last().is_some_and(is_word) {
+            fullname.push(' ');
+        }
+        pending_space = false;
+        fullname.push(c);
+    }
     fullname
 }
 
+/// Paths (relative to `std`, `core` or `alloc`) of the items of the
+/// prelude that may be written in place of the path.
+const PRELUDE_PATHS: &[(&str, &str)] = &[
+    ("string::String", "String"),
+    ("vec::Vec", "Vec"),
+    ("boxed::Box", "Box"),
+    ("option::Option", "Option"),
+    ("result::Result", "Result"),
+];
+
+/// Determines the name of a type as written without paths to items of
+/// the prelude or to primitive types. This allows detecting that e.g.
+/// `String` and `std::string::String` are the same type.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(canonical_type_name("Vec<::std::primitive::u8>"), "Vec<u8>");
+/// ```
+/// Type aliases and types named via different modules cannot be
+/// resolved by the derive macro. For these, see `impl_distinct_types`.
+pub fn canonical_type_name(name: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut canonical = String::new();
+    let mut rest = name;
+    'outer: while let Some(next) = rest.chars().next() {
+        if !canonical.ends_with(|c: char| is_word(c) || c == ':') {
+            for krate in ["::std::", "std::", "::core::", "core::", "::alloc::", "alloc::"].iter() {
+                let path = match rest.strip_prefix(krate) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                if let Some(primitive) = path.strip_prefix("primitive::") {
+                    rest = primitive;
+                    continue 'outer;
+                }
+                for (long, short) in PRELUDE_PATHS.iter() {
+                    match path.strip_prefix(long) {
+                        Some(after) if !after.starts_with(is_word) => {
+                            canonical.push_str(short);
+                            rest = after;
+                            continue 'outer;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        canonical.push(next);
+        rest = &rest[next.len_utf8()..];
+    }
+    canonical
+}
+
+/// Whether the given tokens from the decorated enum mention
+/// any of its generic parameters or `Self`.
+pub fn mentions_generics(ast: &DeriveInput, tokens: proc_macro2::TokenStream) -> bool {
+    mentions_self(tokens.clone()) || ast.generics.params.iter().any(|param| mentions_param(tokens.clone(), param))
+}
+
+/// Whether the marker struct of each variant can be determined from
+/// the type by which it is accessed, i.e. whether no two of these
+/// types can be the same. This is the case if they do not mention
+/// the generic type parameters of the enum and do not differ only in
+/// their lifetimes.
+pub fn markers_inferable(ast: &DeriveInput, types: &[VariantFields<'_>]) -> bool {
+    let depends_on_params = types.iter().any(|fields| {
+        mentions_self(fields.ty())
+            || ast
+                .generics
+                .params
+                .iter()
+                .filter(|param| !matches!(param, GenericParam::Lifetime(_)))
+                .any(|param| mentions_param(fields.ty(), param))
+    });
+    let mut names: Vec<String> = types.iter().map(|fields| erase_lifetimes(&fields.type_name())).collect();
+    names.sort();
+    names.dedup();
+    !depends_on_params && names.len() == types.len()
+}
+
+/// Replaces the lifetimes in a type name by `'_`
+fn erase_lifetimes(name: &str) -> String {
+    let mut erased = String::new();
+    let mut in_lifetime = false;
+    for next in name.chars() {
+        if in_lifetime && (next.is_alphanumeric() || next == '_') {
+            continue;
+        }
+        in_lifetime = next == '\'';
+        erased.push(next);
+        if in_lifetime {
+            erased.push('_');
+        }
+    }
+    erased
+}
+
+/// Parse a reference type, keeping its lifetime
+fn parse_reference(reference: &syn::TypeReference) -> syn::Result<String> {
+    let mut fullname = String::from("&");
+    if let Some(lifetime) = &reference.lifetime {
+        fullname.push_str(&format!("{} ", lifetime));
+    }
+    if reference.mutability.is_some() {
+        fullname.push_str("mut ");
+    }
+    fullname.push_str(&parse_type(&reference.elem)?);
+    Ok(fullname)
+}
+
+/// Parse a raw pointer type
+fn parse_ptr(ptr: &syn::TypePtr) -> syn::Result<String> {
+    let mutability = if ptr.mutability.is_some() { "mut" } else { "const" };
+    Ok(format!("*{} {}", mutability, parse_type(&ptr.elem)?))
+}
+
 /// Parse an array type
-fn parse_array(array: &syn::TypeArray) -> String {
+fn parse_array(array: &syn::TypeArray) -> syn::Result<String> {
     let mut fullname = String::from("[");
-    let inner_ty = parse_type(&array.elem);
+    let inner_ty = parse_type(&array.elem)?;
     fullname.push_str(&inner_ty);
     let len = match &array.len {
         syn::Expr::Lit(syn::ExprLit {
             lit: syn::Lit::Int(int),
             ..
-        }) => int,
-        _ => panic!(
-            "VariantAccess can't be derived on array \
-             types whose length is not expressed in terms of an \
-             integer literal"
-        ),
+        }) => int.base10_digits().to_string(),
+        // constants, const generic parameters and other expressions
+        other => other.to_token_stream().to_string(),
     };
-    fullname.push_str(&format!(";{}]", len.base10_digits()));
-    fullname.retain(|c| c != ' ');
-    fullname
+    fullname.push_str(&format!(";{}]", len));
+    Ok(remove_whitespace(&fullname))
 }
 
 /// Given a tuple, recursive parses each type inside the type
 /// to construct the name of the resulting type
-fn parse_tuple(tuple: &syn::TypeTuple) -> String {
-    let mut fullname = String::from("(");
-    let _ = tuple
-        .elems
-        .pairs()
-        .map(|segment| fullname.push_str(&format!("{},", parse_type(segment.value()))))
-        .collect::<()>();
-    // remove trailing comma
-    let mut fullname = String::from(&fullname[..fullname.len() - 1]);
-    fullname.push_str(")");
-    // remove extraneous whitespace
-    fullname.retain(|c| c != ' ');
-    fullname
+fn parse_tuple(tuple: &syn::TypeTuple) -> syn::Result<String> {
+    if tuple.elems.len() == 1 {
+        // `(T,)` is a tuple whereas `(T)` is not
+        return Ok(format!("({},)", parse_type(&tuple.elems[0])?));
+    }
+    let names = tuple.elems.iter().map(parse_type).collect::<syn::Result<Vec<String>>>()?;
+    Ok(join_tuple(names.into_iter()))
+}
+
+/// Joins the names of the given types into the name of the
+/// tuple type containing them
+fn join_tuple(names: impl Iterator<Item = String>) -> String {
+    remove_whitespace(&format!("({})", names.collect::<Vec<String>>().join(",")))
 }
 
-/// Makes a map of the form < field type : field name >
+/// A variant of the decorated enum together with the names
+/// of the types of its fields.
+///
+/// Variants with more than one field are treated as the tuple
+/// of their field types, e.g. `F1(i64, String)` is accessed as the
+/// type `(i64, String)`. As such a tuple is never stored inside the
+/// enum, it is borrowed as a tuple of references `(&i64, &String)`
+/// instead.
+///
+/// Variants with named fields are accessed as a struct generated
+/// in the module `variant_access_{Enum}` with the same name and fields
+/// as the variant. These are borrowed as the generated structs
+/// `{Variant}Ref` and `{Variant}Mut` whose fields are references.
+pub struct VariantFields<'a> {
+    pub ident: &'a Ident,
+    pub fields: &'a syn::Fields,
+    /// The names of the types of the fields, used for comparing types
+    pub field_types: Vec<String>,
+    /// The module containing the marker structs of the enum
+    pub module: Ident,
+    /// The generic parameters and where clause predicates of the
+    /// enum used by the generated structs of variants with named fields
+    pub generics: syn::Generics,
+    /// The tag type and its name, for variants accessed as
+    /// `Tagged<Tag, T>` rather than by their field types
+    pub tag: Option<(TokenStream2, String)>,
+    /// The enum along with its generic arguments, which replaces `Self`
+    /// in the field types as they are also used in impls for other types
+    pub self_type: TokenStream2,
+}
+
+impl VariantFields<'_> {
+    /// Whether the variant holds exactly one field, which can then be
+    /// borrowed directly.
+    pub fn is_single(&self) -> bool {
+        self.field_types.len() == 1 && !self.is_named()
+    }
+
+    /// Whether the variant has no fields. Such variants are accessed
+    /// through their (zero-sized) marker struct, much like `std::monostate`.
+    pub fn is_unit(&self) -> bool {
+        self.field_types.is_empty()
+    }
+
+    /// Whether the variant has named fields
+    pub fn is_named(&self) -> bool {
+        matches!(self.fields, syn::Fields::Named(_)) && !self.is_unit()
+    }
+
+    /// The marker struct used in the implementations for the variant.
+    /// For variants with named fields, this is the generated struct.
+    pub fn marker(&self) -> TokenStream2 {
+        if self.is_named() || self.is_unit() {
+            self.struct_type(self.ident, false)
+        } else {
+            let module = &self.module;
+            let marker = Ident::new(&self.ident.to_string(), Span::call_site());
+            quote! { #module::#marker }
+        }
+    }
+
+    /// The type by which the variant is accessed. The types of the fields
+    /// keep their spans, so that errors concerning them point at the enum.
+    ///
+    /// # Example
+    /// `F1(i64)` gives `i64`, `F2(i64, String)` gives `(i64, String)`
+    /// and `F3 { x: T }` in `enum Enum<T>` gives `variant_access_Enum::F3<T>`.
+    /// For unit variants, this is their marker struct. Tagged variants
+    /// are accessed as `Tagged<Tag, T>` where `T` is this type.
+    pub fn ty(&self) -> TokenStream2 {
+        let untagged = if self.is_named() || self.is_unit() {
+            self.struct_type(self.ident, false)
+        } else if self.is_single() {
+            self.field_type_tokens().collect()
+        } else {
+            let types = self.field_type_tokens();
+            quote! { (#(#types),*) }
+        };
+        match &self.tag {
+            Some((tag, _)) => quote! { variant_access_traits::Tagged<#tag, #untagged> },
+            None => untagged,
+        }
+    }
+
+    /// The types of the fields, with `Self` replaced by the enum
+    fn field_type_tokens(&self) -> impl Iterator<Item = TokenStream2> + '_ {
+        self.fields
+            .iter()
+            .map(move |field| replace_self(field.ty.to_token_stream(), &self.self_type))
+    }
+
+    /// The name of the type by which the variant is accessed, as
+    /// used for comparing types and in error messages
+    ///
+    /// # Example
+    /// `F1(i64)` gives `i64`, `F2(i64, String)` gives `(i64,String)`
+    /// and `F3 { x: T }` in `enum Enum<T>` gives `variant_access_Enum::F3<T>`.
+    /// For unit variants, this is their marker struct.
+    pub fn type_name(&self) -> String {
+        if let Some((_, tag)) = &self.tag {
+            return format!("Tagged<{},{}>", tag, self.untagged_type_name());
+        }
+        self.untagged_type_name()
+    }
+
+    fn untagged_type_name(&self) -> String {
+        if self.is_named() || self.is_unit() {
+            let args: Vec<String> = self
+                .generics
+                .params
+                .iter()
+                .map(|param| match param {
+                    GenericParam::Type(inner) => inner.ident.to_string(),
+                    GenericParam::Lifetime(inner) => inner.lifetime.to_string(),
+                    GenericParam::Const(inner) => inner.ident.to_string(),
+                })
+                .collect();
+            if args.is_empty() {
+                format!("{}::{}", self.module, self.ident)
+            } else {
+                format!("{}::{}<{}>", self.module, self.ident, args.join(", "))
+            }
+        } else if self.is_single() {
+            self.field_types[0].clone()
+        } else {
+            join_tuple(self.field_types.iter().cloned())
+        }
+    }
+
+    /// The pattern (following the variant name) binding each field of the variant
+    ///
+    /// # Example
+    /// `F1(i64)` gives `(inner)`, `F2(i64, String)` gives `(f0, f1)`
+    /// and `F3 { x: bool }` gives `{ x }`
+    pub fn pattern(&self) -> TokenStream2 {
+        let bindings = self.bindings();
+        if self.is_unit() {
+            quote! { { .. } }
+        } else if self.is_named() {
+            quote! { { #(#bindings),* } }
+        } else if self.is_single() {
+            quote! { (inner) }
+        } else {
+            quote! { (#(#bindings),*) }
+        }
+    }
+
+    /// The value built from the bindings of [`VariantFields::pattern`].
+    /// For variants with named fields, `suffix` determines which of the
+    /// generated structs is built.
+    pub fn value(&self, suffix: &str) -> TokenStream2 {
+        let bindings = self.bindings();
+        if self.is_unit() {
+            self.marker()
+        } else if self.is_named() {
+            let module = &self.module;
+            let name = format_ident!("{}{}", self.ident, suffix, span = Span::call_site());
+            quote! { #module::#name { #(#bindings),* } }
+        } else if self.is_single() {
+            self.tagged(quote! { inner })
+        } else {
+            self.tagged(quote! { (#(#bindings),*) })
+        }
+    }
+
+    /// Tags the given value of the fields, if the variant is tagged
+    fn tagged(&self, value: TokenStream2) -> TokenStream2 {
+        match &self.tag {
+            Some((tag, _)) => quote! { variant_access_traits::Tagged::<#tag, _>::new(#value) },
+            None => value,
+        }
+    }
+
+    /// The arguments (following the variant name) constructing the
+    /// variant from `value` of its field type
+    pub fn construct(&self, value: &Ident) -> TokenStream2 {
+        let value = match self.tag {
+            Some(_) => quote! { #value.value },
+            None => value.to_token_stream(),
+        };
+        if self.is_unit() {
+            quote! { {} }
+        } else if self.is_named() {
+            let bindings = self.bindings();
+            quote! { { #(#bindings: #value.#bindings),* } }
+        } else if self.is_single() {
+            quote! { (#value) }
+        } else {
+            let indices = (0..self.field_types.len()).map(syn::Index::from);
+            quote! { (#(#value.#indices),*) }
+        }
+    }
+
+    /// The reference returned when borrowing the variant from the bindings
+    /// of [`VariantFields::pattern`]. A reference to the marker struct of a
+    /// unit variant does not borrow anything from the enum; as it is zero-sized,
+    /// leaking a box holding it does not allocate.
+    pub fn borrowed_value(&self, mutable: bool) -> TokenStream2 {
+        let marker = self.marker();
+        match (self.is_unit(), mutable) {
+            (true, false) => quote! { &#marker },
+            (true, true) => quote! { std::boxed::Box::leak(std::boxed::Box::new(#marker)) },
+            (false, mutable) if self.is_single() && self.tag.is_some() => {
+                let tag = self.tag.as_ref().map(|(tag, _)| tag);
+                if mutable {
+                    quote! { variant_access_traits::Tagged::<#tag, _>::from_mut(inner) }
+                } else {
+                    quote! { variant_access_traits::Tagged::<#tag, _>::from_ref(inner) }
+                }
+            }
+            (false, _) if self.is_single() => quote! { inner },
+            (false, false) => self.value("Ref"),
+            (false, true) => self.value("Mut"),
+        }
+    }
+
+    /// The type of references to each field of the variant,
+    /// borrowed for the given lifetime. This is either a
+    /// tuple of references or one of the generated structs.
+    pub fn borrowed_type(&self, lifetime: &syn::Lifetime, mutable: bool) -> TokenStream2 {
+        if self.is_named() {
+            let suffix = if mutable { "Mut" } else { "Ref" };
+            let name = format_ident!("{}{}", self.ident, suffix, span = Span::call_site());
+            return self.struct_type(&name, true);
+        }
+        let mutability = if mutable { quote! { mut } } else { quote! {} };
+        let types = self.field_type_tokens();
+        let borrowed = quote! { (#(&#lifetime #mutability #types),*) };
+        match &self.tag {
+            Some((tag, _)) => quote! { variant_access_traits::Tagged<#tag, #borrowed> },
+            None => borrowed,
+        }
+    }
+
+    /// One of the structs generated for a variant with named fields,
+    /// along with its generic arguments, e.g. `variant_access_Enum::F2Ref<'variant_access, T>`
+    fn struct_type(&self, name: &Ident, borrowed: bool) -> TokenStream2 {
+        let module = &self.module;
+        let name = Ident::new(&name.to_string(), Span::call_site());
+        let generics = if borrowed {
+            borrowed_generics(&self.generics)
+        } else {
+            self.generics.clone()
+        };
+        let (_, ty_generics, _) = generics.split_for_impl();
+        quote! { #module::#name #ty_generics }
+    }
+
+    fn bindings(&self) -> Vec<Ident> {
+        match self.fields {
+            syn::Fields::Named(named) => named
+                .named
+                .iter()
+                .filter_map(|field| field.ident.clone())
+                .collect(),
+            _ => (0..self.field_types.len())
+                .map(|ix| format_ident!("f{}", ix))
+                .collect(),
+        }
+    }
+}
+
+/// Replaces `Self` in tokens taken from the decorated enum by `self_type`,
+/// for use in impls for other types than the enum and outside of impls.
+pub fn replace_self(tokens: TokenStream2, self_type: &TokenStream2) -> TokenStream2 {
+    use proc_macro2::{Group, TokenTree};
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(ident) if ident == "Self" => self_type.clone(),
+            TokenTree::Group(group) => {
+                let mut inner = Group::new(group.delimiter(), replace_self(group.stream(), self_type));
+                inner.set_span(group.span());
+                TokenTree::Group(inner).into()
+            }
+            token => token.into(),
+        })
+        .collect()
+}
+
+/// Rewrites relative paths in tokens taken from the decorated enum so
+/// that they can be used inside the module `variant_access_{Enum}`.
+/// Paths starting with `self` or `super` get an extra `super` and
+/// `Self` is replaced by `self_type`.
+fn relative_to_module(
+    tokens: proc_macro2::TokenStream,
+    self_type: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    use proc_macro2::{Group, TokenTree};
+    let mut rewritten = proc_macro2::TokenStream::new();
+    // whether the previous token was the second colon of `::`
+    let mut after_colons = false;
+    let mut colons = 0;
+    for token in tokens {
+        match &token {
+            TokenTree::Ident(ident) if ident == "Self" => {
+                rewritten.extend(self_type.clone());
+            }
+            TokenTree::Ident(ident) if ident == "self" && !after_colons => {
+                rewritten.extend(quote::quote! { super });
+            }
+            TokenTree::Ident(ident) if ident == "super" && !after_colons => {
+                rewritten.extend(quote::quote! { super:: });
+                rewritten.extend(std::iter::once(token.clone()));
+            }
+            TokenTree::Group(group) => {
+                let mut inner = Group::new(
+                    group.delimiter(),
+                    relative_to_module(group.stream(), self_type),
+                );
+                inner.set_span(group.span());
+                rewritten.extend(std::iter::once(TokenTree::Group(inner)));
+            }
+            _ => rewritten.extend(std::iter::once(token.clone())),
+        }
+        colons = match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ':' => colons + 1,
+            _ => 0,
+        };
+        after_colons = colons == 2;
+    }
+    rewritten
+}
+
+/// Determines which of the generic parameters of the enum are
+/// mentioned in the given tokens.
+fn mentions_param(tokens: proc_macro2::TokenStream, param: &GenericParam) -> bool {
+    use proc_macro2::TokenTree;
+    let mut after_quote = false;
+    for token in tokens {
+        let found = match (&token, param) {
+            (TokenTree::Group(group), _) => mentions_param(group.stream(), param),
+            (TokenTree::Ident(ident), GenericParam::Lifetime(inner)) => {
+                after_quote && *ident == inner.lifetime.ident
+            }
+            (TokenTree::Ident(ident), GenericParam::Type(inner)) => *ident == inner.ident,
+            (TokenTree::Ident(ident), GenericParam::Const(inner)) => *ident == inner.ident,
+            _ => false,
+        };
+        if found {
+            return true;
+        }
+        after_quote = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+    }
+    false
+}
+
+/// Makes a list of the variant fields, in the order the variants are declared
+///
+/// Variants marked with `#[variant_access(skip)]` are left out.
 ///
 /// Provides validation
 ///     -- that no field type is used twice.
 ///     -- that input is enum.
-///     -- that all field types do not have named fields
-/// if any of these validations fail, this function panics
-/// and halts compilation
+///     -- that the structs generated for variants with named fields
+///        do not clash with other variants
+///     -- that all field types are supported
+/// if any of these validations fail, an error pointing at the
+/// offending part of the enum is returned. All such errors are
+/// combined so that they can be reported together.
+///
+/// Variants with multiple fields are accessed as the tuple
+/// of their field types.
 ///
 /// # Example
-/// ```
+/// ```ignore
 /// enum Enum {
 ///     F1(i64),
-///     F2(bool)
+///     F2(bool),
+///     F3(i64, String)
 /// }
 /// ```
-/// returns [ < i64: f1 > , < bool: f2 > ]
+/// returns the fields of F1, F2 and F3, of types i64, bool and (i64,String)
 ///
 /// # Example
-/// ```
+/// ```ignore
 /// #[derive(VariantAccess)]
 /// enum Enum {
 ///     F1(i64),
@@ -184,52 +714,231 @@ fn parse_tuple(tuple: &syn::TypeTuple) -> String {
 ///     F3(i64),
 /// }
 /// ```
-/// panics in this function as two distinct fields have type i64.
+/// returns an error pointing at F3 as two distinct fields have type i64.
 ///
 /// # Example
-/// ```
+/// ```ignore
 /// #[derive(VariantAccess)]
 /// enum Enum {
-///     F1(i64, i32),
-///     F2{x: bool}
+///     F1 { x: i64 },
+///     F1Ref(bool)
 /// }
 /// ```
-/// panics in this function as F1 has multiple unnamed types
-/// or because F2 has a named field.
-pub fn fetch_types_from_enum(ast: &DeriveInput) -> HashMap<String, &Ident> {
-    let mut types: HashMap<String, &Ident> = HashMap::new();
-
-    if let Data::Enum(data) = &ast.data {
-        for var in data.variants.iter() {
-            if let syn::Fields::Unnamed(field_) = &var.fields {
-                if field_.unnamed.len() > 1 {
-                    panic!("Can only derive for enums whose types do not contain multiple fields.");
-                }
+/// returns an error pointing at F1Ref as it clashes with the struct
+/// generated for the named fields of F1.
+pub fn fetch_types_from_enum(ast: &DeriveInput) -> syn::Result<Vec<VariantFields<'_>>> {
+    let mut types: Vec<VariantFields> = vec![];
+    let module = Ident::new(&format!("variant_access_{}", ast.ident), Span::call_site());
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let self_type = quote! { #ident #ty_generics };
+    let mut errors: Vec<syn::Error> = vec![];
 
-                for field_entry in field_.unnamed.iter() {
-                    if types
-                        .insert(parse_type(&field_entry.ty), &var.ident)
-                        .is_some()
+    let data = match &ast.data {
+        Data::Enum(data) => data,
+        Data::Struct(data) => return Err(not_enum_error(data.struct_token)),
+        Data::Union(data) => return Err(not_enum_error(data.union_token)),
+    };
+    let empty = syn::punctuated::Punctuated::new();
+    let skipped = |var: &syn::Variant| fetch_variant_options(var).is_ok_and(|options| options.skip);
+    for var in data.variants.iter() {
+        let options = match fetch_variant_options(var) {
+            Ok(options) if options.skip => continue,
+            Ok(options) => options,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let (entries, generics) = match &var.fields {
+            syn::Fields::Unnamed(field_) => (&field_.unnamed, syn::Generics::default()),
+            syn::Fields::Named(field_) if !field_.named.is_empty() => {
+                for suffix in ["Ref", "Mut"].iter() {
+                    let generated = format!("{}{}", var.ident, suffix);
+                    if let Some(other) = data
+                        .variants
+                        .iter()
+                        .find(|other| other.ident == generated && !skipped(other))
                     {
-                        panic!("Cannot derive VariantAccess for enum with multiple fields of same type");
+                        errors.push(syn::Error::new_spanned(
+                            &other.ident,
+                            format!(
+                                "VariantAccess generates a struct named `{0}` for the named fields of \
+                                 variant `{1}`, which clashes with variant `{0}`; rename one of the variants",
+                                generated, var.ident
+                            ),
+                        ));
                     }
                 }
-            } else {
-                panic!("Cannot derive VariantAccess for enums whose types have named fields.")
+                (&field_.named, used_generics(ast, var))
+            }
+            syn::Fields::Named(_) | syn::Fields::Unit => (&empty, syn::Generics::default()),
+        };
+        let mut field_types = vec![];
+        for field_entry in entries.iter() {
+            match parse_type(&field_entry.ty) {
+                Ok(type_) => field_types.push(type_),
+                Err(error) => errors.push(error),
             }
         }
-    } else {
-        panic!("Can only derive VariantAccess for enums.")
-    };
-    types
+        if field_types.len() < entries.len() {
+            continue;
+        }
+        let tag = match options.tag {
+            Some(tag) if !matches!(var.fields, syn::Fields::Unnamed(_)) || field_types.is_empty() => {
+                errors.push(syn::Error::new_spanned(
+                    tag.keyword,
+                    "only variants with unnamed fields can be tagged, as the types \
+                     generated for other variants are distinct already",
+                ));
+                continue;
+            }
+            Some(TagOption { ty: Some(ty), .. }) => match parse_type(&ty) {
+                Ok(name) => Some((ty.to_token_stream(), name)),
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            },
+            Some(TagOption { ty: None, .. }) => {
+                let marker = Ident::new(&var.ident.to_string(), Span::call_site());
+                Some((quote! { #module::#marker }, format!("{}::{}", module, marker)))
+            }
+            None => None,
+        };
+        let fields = VariantFields {
+            ident: &var.ident,
+            fields: &var.fields,
+            field_types,
+            module: module.clone(),
+            generics,
+            tag,
+            self_type: self_type.clone(),
+        };
+        let canonical = canonical_type_name(&fields.type_name());
+        if let Some(other) = types
+            .iter()
+            .find(|other| canonical_type_name(&other.type_name()) == canonical)
+        {
+            errors.push(syn::Error::new_spanned(
+                &var.fields,
+                format!(
+                    "Cannot derive VariantAccess for enum with multiple fields of same type: \
+                     variants `{}` and `{}` both have type `{}`; mark one of them with \
+                     `#[variant_access(tag)]` or wrap it in a newtype struct to give each \
+                     variant a distinct type",
+                    other.ident, fields.ident, canonical
+                ),
+            ));
+            continue;
+        }
+        types.push(fields);
+    }
+    match errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    }) {
+        Some(error) => Err(error),
+        None => Ok(types),
+    }
+}
+
+/// The error for decorating a struct or union, pointing at its keyword
+fn not_enum_error(keyword: impl ToTokens) -> syn::Error {
+    syn::Error::new_spanned(
+        keyword,
+        "VariantAccess can only be derived for enums; \
+         use an enum with one variant per type instead",
+    )
+}
+
+/// Finds the generics of the enum needed by the structs generated for
+/// a variant with named fields. These are the parameters mentioned by
+/// the fields, along with those mentioned in their bounds, and the
+/// predicates of the where clause constraining any of these.
+fn used_generics(ast: &DeriveInput, var: &syn::Variant) -> syn::Generics {
+    let mut mentioned = var.fields.to_token_stream();
+    if mentions_self(mentioned.clone()) {
+        return ast.generics.clone();
+    }
+    let predicates: Vec<&syn::WherePredicate> = ast
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|clause| clause.predicates.iter())
+        .collect();
+    let mut used: Vec<&GenericParam> = vec![];
+    let mut used_predicates: Vec<&syn::WherePredicate> = vec![];
+    loop {
+        let newly_used: Vec<&GenericParam> = ast
+            .generics
+            .params
+            .iter()
+            .filter(|param| !is_among(param, &used) && mentions_param(mentioned.clone(), param))
+            .collect();
+        for param in newly_used.iter() {
+            mentioned.extend(param.to_token_stream());
+            used.push(param);
+        }
+        let newly_used_predicates: Vec<&syn::WherePredicate> = predicates
+            .iter()
+            .filter(|predicate| {
+                !used_predicates.iter().any(|other| std::ptr::eq(*other, **predicate))
+                    && used
+                        .iter()
+                        .any(|param| mentions_param(predicate.to_token_stream(), param))
+            })
+            .copied()
+            .collect();
+        for predicate in newly_used_predicates.iter() {
+            mentioned.extend(predicate.to_token_stream());
+            used_predicates.push(predicate);
+        }
+        if newly_used.is_empty() && newly_used_predicates.is_empty() {
+            break;
+        }
+    }
+    // keep the order of declaration
+    let mut generics = ast.generics.clone();
+    generics.params = ast
+        .generics
+        .params
+        .iter()
+        .filter(|param| is_among(param, &used))
+        .cloned()
+        .collect();
+    generics.where_clause = ast.generics.where_clause.as_ref().map(|clause| syn::WhereClause {
+        where_token: clause.where_token,
+        predicates: predicates
+            .iter()
+            .filter(|predicate| used_predicates.iter().any(|other| std::ptr::eq(*other, **predicate)))
+            .map(|predicate| (*predicate).clone())
+            .collect(),
+    });
+    generics
+}
+
+/// Whether the parameter is one of the given parameters of the same enum
+fn is_among(param: &GenericParam, params: &[&GenericParam]) -> bool {
+    params.iter().any(|other| std::ptr::eq(*other, param))
+}
+
+/// Whether the given tokens refer to `Self`
+fn mentions_self(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Group(group) => mentions_self(group.stream()),
+        proc_macro2::TokenTree::Ident(ident) => ident == "Self",
+        _ => false,
+    })
 }
 
 /// In order to avoid conflicting definitions of the GetVariant / SetVariant traits for
 /// enums that are generic over more than one parameter, we use marker structs:
 /// see https://stackoverflow.com/questions/52281091/can-i-avoid-eager-ambiguity-resolution-for-trait-implementations-with-generics/52692592#52692592
-/// This function generates these structs and places them in a private module.
+/// This function generates these structs and places them in a module with the
+/// same visibility as the enum.
 /// # Example
-/// ```
+/// ```ignore
 /// #[derive(VariantAccess)]
 /// enum Enum<X, Y> {
 ///     F1(X),
@@ -237,27 +946,199 @@ pub fn fetch_types_from_enum(ast: &DeriveInput) -> HashMap<String, &Ident> {
 /// }
 /// ```
 /// produces the following module
+/// ```ignore
+/// #[allow(non_snake_case)]
+/// mod variant_access_Enum {
+///     pub struct F1;
+///     pub struct F2;
+/// }
+/// ```
+///
+/// For variants with named fields, the generated struct holding the
+/// fields doubles as the marker struct. Structs holding references to
+/// the fields are generated as well.
+/// # Example
+/// ```ignore
+/// #[derive(VariantAccess)]
+/// enum Enum<X> {
+///     F1(X),
+///     F2 { x: X, y: bool }
+/// }
 /// ```
+/// produces the following module
+/// ```ignore
 /// #[allow(non_snake_case)]
 /// mod variant_access_Enum {
-///     pub (crate) struct F1;
-///     pub (crate) struct F2;
+///     pub struct F1;
+///     mod variant_access_F2 {
+///         use super::super::*;
+///         pub struct __VariantAccessFields<X> { pub x: X, pub y: bool }
+///         pub struct __VariantAccessFieldsRef<'variant_access, X> { pub x: &'variant_access X, pub y: &'variant_access bool }
+///         pub struct __VariantAccessFieldsMut<'variant_access, X> { pub x: &'variant_access mut X, pub y: &'variant_access mut bool }
+///     }
+///     pub use variant_access_F2::{
+///         __VariantAccessFields as F2,
+///         __VariantAccessFieldsRef as F2Ref,
+///         __VariantAccessFieldsMut as F2Mut,
+///     };
 /// }
 /// ```
-pub fn create_marker_structs(name: &str, types: &HashMap<String, &Ident>) -> TokenStream {
-    let mut piece = format!("#[allow(non_snake_case)]\n mod variant_access_{}", name);
-    piece.push_str("{ ");
-    for field_ in types.values() {
-        piece.push_str(&format!("pub (crate) struct {};", field_.to_string()));
-    }
-    piece.push_str("} ");
-    piece.parse().unwrap()
+/// The structs holding the fields are declared in a module of their own,
+/// under names that cannot be mistaken for the types of the fields, as the
+/// marker structs would otherwise shadow types of the same name.
+pub fn create_marker_structs(ast: &DeriveInput, types: &[VariantFields<'_>]) -> TokenStream2 {
+    let vis = &ast.vis;
+    let module = Ident::new(&format!("variant_access_{}", ast.ident), Span::call_site());
+    // `Self` in the fields of the enum refers to the enum itself
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let ident = &ast.ident;
+    let self_type = quote! { super::#ident #ty_generics };
+    let structs = types.iter().map(|fields| {
+        let marker = Ident::new(&fields.ident.to_string(), Span::call_site());
+        if let (syn::Fields::Named(named), true) = (fields.fields, fields.is_named()) {
+            create_named_structs(fields, named, &self_type)
+        } else if fields.is_unit() {
+            quote! {
+                #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                pub struct #marker;
+            }
+        } else {
+            quote! { pub struct #marker; }
+        }
+    });
+    quote! {
+        #[allow(non_snake_case)]
+        #vis mod #module {
+            #(#structs)*
+        }
+    }
+}
+
+/// Generates the structs holding the fields of a variant with named fields,
+/// along with the structs holding references to those fields.
+///
+/// The owned struct implements `Debug`, `Clone` and `PartialEq` whenever the
+/// types of all fields do. The borrowing structs implement `Debug` likewise
+/// and the struct of shared references is `Copy`. These bounds are wrapped
+/// in a higher-ranked `for<'variant_access_bound>` so that they are only
+/// checked when the impls are used.
+fn create_named_structs(
+    fields: &VariantFields<'_>,
+    named: &syn::FieldsNamed,
+    self_type: &TokenStream2,
+) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    // The structs are declared two modules below the enum
+    let relative = |tokens: TokenStream2| relative_to_module(relative_to_module(tokens, self_type), self_type);
+    let predicates: Vec<TokenStream2> = fields
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|clause| clause.predicates.iter())
+        .map(|predicate| relative(predicate.to_token_stream()))
+        .collect();
+    let where_clause = if predicates.is_empty() {
+        TokenStream2::new()
+    } else {
+        quote! { where #(#predicates),* }
+    };
+    let field_names: Vec<&Ident> = named.named.iter().filter_map(|field| field.ident.as_ref()).collect();
+    let field_types: Vec<TokenStream2> = named
+        .named
+        .iter()
+        .map(|field| relative(field.ty.to_token_stream()))
+        .collect();
+    let owned = fields.generics.clone();
+    let borrowed = borrowed_generics(&fields.generics);
+    let variants = [
+        ("", &owned, quote! {}),
+        ("Ref", &borrowed, quote! { &'variant_access }),
+        ("Mut", &borrowed, quote! { &'variant_access mut }),
+    ];
+    let mut exports = vec![];
+    for (suffix, generics, reference) in variants.iter() {
+        let name = format_ident!("__VariantAccessFields{}", suffix);
+        let export = format_ident!("{}{}", fields.ident, suffix, span = Span::call_site());
+        exports.push(quote! { #name as #export });
+        let (impl_generics, ty_generics, _) = generics.split_for_impl();
+        let impl_generics = relative(impl_generics.to_token_stream());
+        tokens.extend(quote! {
+            pub struct #name #impl_generics #where_clause {
+                #(pub #field_names: #reference #field_types),*
+            }
+        });
+
+        let bounded = |trait_: TokenStream2| -> TokenStream2 {
+            quote! {
+                impl #impl_generics #trait_ for #name #ty_generics
+                where
+                    #(#predicates,)*
+                    #(for<'variant_access_bound> #reference #field_types: #trait_),*
+            }
+        };
+        let debug_impl = bounded(quote! { std::fmt::Debug });
+        let debug_name = export.to_string();
+        let debug_fields = field_names
+            .iter()
+            .map(|name| name.to_string().trim_start_matches("r#").to_string());
+        tokens.extend(quote! {
+            #debug_impl {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct(#debug_name)
+                        #(.field(#debug_fields, &self.#field_names))*
+                        .finish()
+                }
+            }
+        });
+        match *suffix {
+            "" => {
+                let clone_impl = bounded(quote! { std::clone::Clone });
+                let eq_impl = bounded(quote! { std::cmp::PartialEq });
+                tokens.extend(quote! {
+                    #clone_impl {
+                        fn clone(&self) -> Self {
+                            #name { #(#field_names: std::clone::Clone::clone(&self.#field_names)),* }
+                        }
+                    }
+                    #eq_impl {
+                        fn eq(&self, other: &Self) -> bool {
+                            #(self.#field_names == other.#field_names &&)* true
+                        }
+                    }
+                });
+            }
+            "Ref" => {
+                tokens.extend(quote! {
+                    impl #impl_generics std::clone::Clone for #name #ty_generics #where_clause {
+                        fn clone(&self) -> Self {
+                            *self
+                        }
+                    }
+                    impl #impl_generics std::marker::Copy for #name #ty_generics #where_clause {}
+                });
+            }
+            _ => {}
+        }
+    }
+    let module = format_ident!("variant_access_{}", fields.ident, span = Span::call_site());
+    quote! {
+        mod #module {
+            #[allow(unused_imports)]
+            use super::super::*;
+            #tokens
+        }
+        pub use #module::{#(#exports),*};
+    }
 }
 
 #[cfg(test)]
 mod test_parsers {
     use super::*;
 
+    fn by_type_name<'a, 'b>(types: &'b [VariantFields<'a>], name: &str) -> &'b VariantFields<'a> {
+        types.iter().find(|fields| fields.type_name() == name).unwrap()
+    }
+
     #[test]
     fn test_parse_tuple() {
         let ast: DeriveInput = syn::parse_str(
@@ -268,8 +1149,8 @@ mod test_parsers {
         "#,
         )
         .unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["(i64,bool)"]);
     }
 
@@ -283,8 +1164,8 @@ mod test_parsers {
         "#,
         )
         .unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["[u8;32]"]);
     }
 
@@ -297,8 +1178,8 @@ mod test_parsers {
             }
         "#,
         ).unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["[(i32,bool);32]"]);
     }
 
@@ -311,8 +1192,8 @@ mod test_parsers {
             }
         "#,
         ).unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["(i32,[u8;32])"]);
     }
 
@@ -325,11 +1206,225 @@ mod test_parsers {
             }
         "#,
         ).unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["[(T,[u8;32]);12]"]);
     }
 
+    #[test]
+    fn test_multiple_fields() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum TupleTest {
+                F1(i64, [u8; 4]),
+            }
+        "#,
+        ).unwrap();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
+        assert_eq!(type_names, vec!["(i64,[u8;4])"]);
+        let fields = by_type_name(&types, "(i64,[u8;4])");
+        let lifetime: syn::Lifetime = syn::parse_str("'a").unwrap();
+        assert_eq!(fields.ty().to_string(), quote! { (i64, [u8; 4]) }.to_string());
+        assert_eq!(fields.pattern().to_string(), quote! { (f0, f1) }.to_string());
+        assert_eq!(
+            fields.construct(&format_ident!("value")).to_string(),
+            quote! { (value.0, value.1) }.to_string()
+        );
+        assert_eq!(
+            fields.borrowed_type(&lifetime, false).to_string(),
+            quote! { (&'a i64, &'a [u8; 4]) }.to_string()
+        );
+        assert_eq!(
+            fields.borrowed_type(&lifetime, true).to_string(),
+            quote! { (&'a mut i64, &'a mut [u8; 4]) }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_named_fields() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum NamedTest<T, U: Into<T>, V> {
+                F1 { x: bool, y: U },
+                F2(V),
+            }
+        "#,
+        ).unwrap();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let fields = by_type_name(&types, "variant_access_NamedTest::F1<T, U>");
+        let lifetime: syn::Lifetime = syn::parse_str("'a").unwrap();
+        assert_eq!(fields.pattern().to_string(), quote! { { x, y } }.to_string());
+        assert_eq!(
+            fields.value("Ref").to_string(),
+            quote! { variant_access_NamedTest::F1Ref { x, y } }.to_string()
+        );
+        assert_eq!(
+            fields.construct(&format_ident!("value")).to_string(),
+            quote! { { x: value.x, y: value.y } }.to_string()
+        );
+        assert_eq!(
+            fields.borrowed_type(&lifetime, true).to_string(),
+            quote! { variant_access_NamedTest::F1Mut<'variant_access, T, U> }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_relative_to_module() {
+        let self_type = quote::quote! { super::Enum };
+        let ty: syn::Type = syn::parse_str("(self::A, super::B, crate::C<super::D>, Box<Self>)").unwrap();
+        let rewritten = relative_to_module(ty.to_token_stream(), &self_type);
+        let expected: syn::Type = syn::parse_str(
+            "(super::A, super::super::B, crate::C<super::super::D>, Box<super::Enum>)"
+        ).unwrap();
+        assert_eq!(rewritten.to_string(), expected.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn test_references() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum Token<'a, T> {
+                F1(&'a str),
+                F2(&'a mut [T; 2]),
+                F3(&'static Vec<&'a T>),
+            }
+        "#,
+        ).unwrap();
+        let (name, params) = fetch_name_with_generic_params(&ast);
+        assert_eq!(name, "Token<'a,T>");
+        assert_eq!(params, vec!["'a", "T"]);
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
+        assert_eq!(type_names, vec!["&'a str", "&'a mut [T;2]", "&'static Vec<&'a T>"]);
+        assert_eq!(remove_whitespace("( & 'b mut Vec < T > , usize )"), "(&'b mut Vec<T>,usize)");
+    }
+
+    #[test]
+    fn test_type_grammar() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum Enum<T: Iterator> {
+                F1(&'static [u8]),
+                F2(fn(i32) -> i32),
+                F3(Box<(dyn Fn() + Send)>),
+                F4((i64)),
+                F5((bool,)),
+                F6(<T as std::iter::Iterator>::Item),
+                F7([u8; LEN * 2]),
+                F8(*const ::std::string::String),
+                F9(!),
+            }
+        "#,
+        )
+        .unwrap();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
+        assert_eq!(
+            type_names,
+            vec![
+                "&'static [u8]",
+                "fn(i32)->i32",
+                "Box<(dyn Fn()+Send)>",
+                "i64",
+                "(bool,)",
+                "<T as std::iter::Iterator>::Item",
+                "[u8;LEN*2]",
+                "*const ::std::string::String",
+                "!",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canonical_type_name() {
+        assert_eq!(canonical_type_name("std::string::String"), "String");
+        assert_eq!(canonical_type_name("Vec<::std::primitive::u8>"), "Vec<u8>");
+        assert_eq!(
+            canonical_type_name("(alloc::vec::Vec<i32>,core::option::Option<bool>)"),
+            "(Vec<i32>,Option<bool>)"
+        );
+        assert_eq!(canonical_type_name("my::std::string::String"), "my::std::string::String");
+        assert_eq!(canonical_type_name("std::string::StringBuilder"), "std::string::StringBuilder");
+        assert_eq!(canonical_type_name("mystd::vec::Vec<T>"), "mystd::vec::Vec<T>");
+    }
+
+    #[test]
+    fn test_markers_inferable() {
+        let inferable = [
+            "enum Enum { F1(i64), F2(bool) }",
+            "enum Enum<'a> { F1(&'a str), F2(&'a [u8]) }",
+            "enum Enum<T> { F1(i64), F2(Vec<i64>), F3 { x: bool }, F4 }",
+        ];
+        let not_inferable = [
+            "enum Enum<T> { F1(i64), F2(T) }",
+            "enum Enum<T> { F1 { x: T }, F2(bool) }",
+            "enum Enum<'a> { F1(&'a str), F2(&'static str) }",
+        ];
+        for (source, expected) in inferable
+            .iter()
+            .map(|source| (source, true))
+            .chain(not_inferable.iter().map(|source| (source, false)))
+        {
+            let ast: DeriveInput = syn::parse_str(source).unwrap();
+            let types = fetch_types_from_enum(&ast).unwrap();
+            assert_eq!(markers_inferable(&ast, &types), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "variants `F1` and `F2` both have type `String`")]
+    fn test_prelude_path_collision() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum Enum {
+                F1(String),
+                F2(std::string::String),
+            }
+        "#,
+        )
+        .unwrap();
+        fetch_types_from_enum(&ast).unwrap();
+    }
+
+    #[test]
+    fn test_generics() {
+        let ast: DeriveInput = syn::parse_str(
+            r#"
+            enum Enum<T: Clone = i32, const N: usize> where T: Debug {
+                F1([T; N]),
+                F2 { x: T },
+                F3 { y: bool },
+            }
+        "#,
+        )
+        .unwrap();
+        let (name, _) = fetch_name_with_generic_params(&ast);
+        assert_eq!(name, "Enum<T,N>");
+        let generics = borrowed_generics(&ast.generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        assert_eq!(
+            impl_generics.to_token_stream().to_string(),
+            quote! { <'variant_access, T: Clone, const N: usize> }.to_string()
+        );
+        assert_eq!(
+            ty_generics.to_token_stream().to_string(),
+            quote! { <'variant_access, T, N> }.to_string()
+        );
+        assert_eq!(
+            where_clause.to_token_stream().to_string(),
+            quote! { where T: Debug }.to_string()
+        );
+        let types = fetch_types_from_enum(&ast).unwrap();
+        assert_eq!(types[0].type_name(), "[T;N]");
+        let (impl_generics, _, where_clause) = by_type_name(&types, "variant_access_Enum::F2<T>").generics.split_for_impl();
+        assert_eq!(impl_generics.to_token_stream().to_string(), quote! { <T: Clone> }.to_string());
+        assert_eq!(where_clause.to_token_stream().to_string(), quote! { where T: Debug }.to_string());
+        let (impl_generics, _, where_clause) = by_type_name(&types, "variant_access_Enum::F3").generics.split_for_impl();
+        assert_eq!(impl_generics.to_token_stream().to_string(), "");
+        assert_eq!(where_clause.to_token_stream().to_string(), "");
+    }
+
     #[test]
     fn test_nested_generics() {
         let ast: DeriveInput = syn::parse_str(
@@ -339,8 +1434,8 @@ mod test_parsers {
             }
         "#,
         ).unwrap();
-        let types = fetch_types_from_enum(&ast);
-        let type_names: Vec<_> = types.keys().collect();
+        let types = fetch_types_from_enum(&ast).unwrap();
+        let type_names: Vec<_> = types.iter().map(VariantFields::type_name).collect();
         assert_eq!(type_names, vec!["(Box<T>,PhantomData<H>)"]);
     }
 }
@@ -1,45 +1,163 @@
+use std::any::TypeId;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 use std::{error::Error, fmt};
 
-/// Custom errors for this crate. Keeps a record of
-/// the enum and requested type that produced the error
-#[derive(Debug)]
-pub struct VariantAccessError {
-    enum_name: String,
-    requested_type: String,
-    msg: String,
+/// The kind of a [`VariantAccessError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The enum has a field of the requested type, but another
+    /// field is active
+    WrongActiveField,
+    /// No field of the enum has the requested type
+    InvalidType,
+}
+
+/// Custom errors for this crate. Keeps a record of the enum, the
+/// requested type and the type of the active field that produced the
+/// error. The names of the types are given by `std::any::type_name`,
+/// so they are meant for diagnostics only.
+///
+/// Errors of methods consuming the enum, such as
+/// [`GetVariant::get_variant`], hold on to it, so that it can be
+/// recovered with [`into_inner`](VariantAccessError::into_inner).
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, PartialEq, Debug)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let error = GetVariant::<bool, _>::get_variant(instance).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::WrongActiveField);
+/// assert_eq!(error.requested_type(), "bool");
+/// assert_eq!(error.active_type(), "i64");
+/// assert_eq!(error.into_inner(), Enum::F1(42));
+/// ```
+pub struct VariantAccessError<Inner = ()> {
+    kind: ErrorKind,
+    enum_name: &'static str,
+    requested_type: &'static str,
+    active_type: &'static str,
+    inner: Inner,
 }
 
 impl VariantAccessError {
-    /// Makes the appropriate error message for when get_variant fails
-    pub fn wrong_active_field(type_name: &str, requested_type: &str) -> VariantAccessError {
+    /// Makes the appropriate error for when get_variant fails
+    pub fn wrong_active_field(
+        enum_name: &'static str,
+        requested_type: &'static str,
+        active_type: &'static str,
+    ) -> VariantAccessError {
         VariantAccessError {
-            enum_name: type_name.to_string(),
-            requested_type: requested_type.to_string(),
-            msg: format!(
-                "Active field of enum <{}> is not of requested type <{}>",
-                type_name, requested_type
-            ),
+            kind: ErrorKind::WrongActiveField,
+            enum_name,
+            requested_type,
+            active_type,
+            inner: (),
         }
     }
 
-    /// Makes the appropriate error message for when has_variant fails
-    pub fn invalid_type(type_name: &str, requested_type: &str) -> VariantAccessError {
+    /// Makes the appropriate error for when has_variant fails
+    pub fn invalid_type(
+        enum_name: &'static str,
+        requested_type: &'static str,
+        active_type: &'static str,
+    ) -> VariantAccessError {
         VariantAccessError {
-            enum_name: type_name.to_string(),
-            requested_type: requested_type.to_string(),
-            msg: format!(
-                "Requested type <{}> does not match the type of any field \
-                           in enum <{}>",
-                requested_type, type_name
-            ),
+            kind: ErrorKind::InvalidType,
+            enum_name,
+            requested_type,
+            active_type,
+            inner: (),
+        }
+    }
+
+    /// Attaches the value that could not be accessed to the error
+    pub fn with_inner<Inner>(self, inner: Inner) -> VariantAccessError<Inner> {
+        VariantAccessError {
+            kind: self.kind,
+            enum_name: self.enum_name,
+            requested_type: self.requested_type,
+            active_type: self.active_type,
+            inner,
+        }
+    }
+}
+
+impl<Inner> VariantAccessError<Inner> {
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn enum_name(&self) -> &'static str {
+        self.enum_name
+    }
+
+    pub fn requested_type(&self) -> &'static str {
+        self.requested_type
+    }
+
+    pub fn active_type(&self) -> &'static str {
+        self.active_type
+    }
+
+    /// Gives back the value that could not be accessed, e.g. the enum
+    /// consumed by [`GetVariant::get_variant`]
+    pub fn into_inner(self) -> Inner {
+        self.inner
+    }
+
+    /// Drops the value that could not be accessed, keeping the
+    /// description of the error
+    pub fn without_inner(self) -> VariantAccessError {
+        VariantAccessError {
+            kind: self.kind,
+            enum_name: self.enum_name,
+            requested_type: self.requested_type,
+            active_type: self.active_type,
+            inner: (),
         }
     }
 }
-impl Error for VariantAccessError {}
 
-impl fmt::Display for VariantAccessError {
+// Not derived, so that the enum held by the error need not implement Debug
+impl<Inner> fmt::Debug for VariantAccessError<Inner> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("VariantAccessError")
+            .field("kind", &self.kind)
+            .field("enum_name", &self.enum_name)
+            .field("requested_type", &self.requested_type)
+            .field("active_type", &self.active_type)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Inner> Error for VariantAccessError<Inner> {}
+
+impl<Inner> fmt::Display for VariantAccessError<Inner> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "VariantAccessError :: {}", self.msg)
+        match self.kind {
+            ErrorKind::WrongActiveField => write!(
+                f,
+                "VariantAccessError :: Active field of enum <{}> is of type <{}>, not of requested type <{}>",
+                self.enum_name, self.active_type, self.requested_type
+            ),
+            ErrorKind::InvalidType => write!(
+                f,
+                "VariantAccessError :: Requested type <{}> does not match the type of any field \
+                 in enum <{}>",
+                self.requested_type, self.enum_name
+            ),
+        }
     }
 }
 
@@ -61,10 +179,10 @@ impl fmt::Display for VariantAccessError {
 ///    F1(i64),
 ///    F2(bool)
 ///}
-/// let result: bool = has_variant::<i64>(); // assigns true to result
-/// let result: bool = has_variant::<i32>(); // assigns false to result
-///
 /// let instance = Enum::F1(42);
+/// let result: bool = instance.has_variant::<i64>(); // assigns true to result
+/// let result: bool = instance.has_variant::<i32>(); // assigns false to result
+///
 /// let result = instance.contains_variant::<i64>(); // result has value Ok(true)
 /// let result = instance.contains_variant::<bool>(); // result has value Ok(false)
 /// let result = instance.contains_variant::<i32>(); // result has value Err
@@ -74,14 +192,178 @@ pub trait ContainsVariant {
     fn contains_variant<T: 'static>(&self) -> Result<bool, VariantAccessError>;
 }
 
+/// This trait is for describing the active field of an enum,
+/// much like `index` of `std::variant`, e.g. for logging.
+///
+/// variant_index gives the position of the active variant in the
+/// declaration of the enum, counting from zero. variant_name gives
+/// its name, and active_type_name and active_type_id describe the
+/// type by which it is accessed.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+///enum Enum {
+///    F1(i64),
+///    F2(bool)
+///}
+/// let instance = Enum::F2(true);
+/// let result = instance.variant_index(); // assigns 1 to result
+/// let result = instance.variant_name(); // assigns "F2" to result
+/// let result = instance.active_type_name(); // assigns "bool" to result
+/// let result = instance.active_type_id(); // assigns std::any::TypeId::of::<bool>() to result
+/// ```
+///
+/// The type name is given by `std::any::type_name`, so it is
+/// meant for diagnostics only. Variants skipped by the derive
+/// macro are described by the tuple of their field types.
+pub trait ActiveVariant {
+    fn variant_index(&self) -> usize;
+    fn variant_name(&self) -> &'static str;
+    fn active_type_name(&self) -> &'static str;
+    fn active_type_id(&self) -> TypeId;
+}
+
+/// This trait is for listing the alternatives of an enum without
+/// an instance of it, e.g. to offer a choice between them.
+///
+/// VARIANT_COUNT is the number of alternatives. type_ids, type_names
+/// and variant_names describe each of them, in the order they are
+/// declared. index_of gives the position of the alternative of the
+/// specified type in these lists, if there is one.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+///enum Enum {
+///    F1(i64),
+///    F2(bool)
+///}
+/// let result = Enum::VARIANT_COUNT; // assigns 2 to result
+/// let result = Enum::type_names(); // assigns vec!["i64", "bool"] to result
+/// let result = Enum::variant_names(); // assigns ["F1", "F2"] to result
+/// let result = Enum::index_of::<bool>(); // assigns Some(1) to result
+/// let result = Enum::index_of::<i32>(); // assigns None to result
+/// ```
+///
+/// Variants skipped by the derive macro are not alternatives, so in
+/// their presence these indices may differ from
+/// [`ActiveVariant::variant_index`].
+pub trait VariantTypes {
+    const VARIANT_COUNT: usize;
+    fn type_ids() -> Vec<TypeId>;
+    fn type_names() -> Vec<&'static str>;
+    fn variant_names() -> &'static [&'static str];
+
+    fn index_of<T: 'static>() -> Option<usize> {
+        Self::type_ids()
+            .iter()
+            .position(|id| *id == TypeId::of::<T>())
+    }
+}
+
+/// This trait is for querying the active field of an enum
+/// without relying on `std::any::TypeId`.
+///
+/// is_variant determines if the active field is of the specified type.
+/// Unlike [`ContainsVariant`], it is resolved at compile time and so
+/// also works for enums borrowing data with non-`'static` lifetimes.
+/// Asking about a type that no field has will not compile.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Token<'a> {
+///     Ident(&'a str),
+///     Num(i64)
+/// }
+/// let source = String::from("x");
+/// let instance = Token::Ident(&source);
+/// let result = IsVariant::<&str, _>::is_variant(&instance); // assigns true to result
+/// let result = IsVariant::<i64, _>::is_variant(&instance); // assigns false to result
+/// // IsVariant::<i32, _>::is_variant(&instance); // will not compile as Token has no field of type i32
+/// ```
+///
+/// This trait has a generic parameter `Marker` for adding marker
+/// structs. This is used if implementing this trait for enums with
+/// more than one generic parameter in order to avoid definition clashes.
+pub trait IsVariant<T, Marker = ()> {
+    fn is_variant(&self) -> bool;
+}
+
+/// This trait is for extracting the raw values in an enum
+///
+/// get_variant consumes the enum and returns the raw value of
+/// the active field if it has the same type as the specified type.
+/// Otherwise, an Err should be returned, from which the enum can
+/// be recovered with [`VariantAccessError::into_inner`]. It is intended
+/// to use this function in conjunction with [`has_variant`] /
+/// [`contains_variant`] to know that safe unwrapping can occur.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let instance = Enum::F1(42);
+///
+/// let inner: i64 = instance.get_variant().unwrap(); // assigns 42 to inner
+/// // let inner: bool = instance.get_variant().unwrap() // panics because of unhandled Err.
+/// // let inner: i32 = instance.get_variant().unwrap() // will not compile as GetVariant<i32> is not implemented for Enum.
+/// ```
+///
+/// Variants with more than one field are treated as the tuple of
+/// their field types. As such a tuple is never stored in the enum,
+/// it cannot be borrowed. Instead, this trait is also implemented
+/// for references to the enum, returning a tuple of references to
+/// the fields (much like `IntoIterator` is implemented for `&Vec<T>`).
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64, String),
+///     F2(bool)
+/// }
+/// let mut instance = Enum::F1(42, String::from("42"));
+///
+/// let (num, string): (&i64, &String) = (&instance).get_variant().unwrap();
+/// let (num, string): (&mut i64, &mut String) = (&mut instance).get_variant().unwrap();
+/// let inner: (i64, String) = instance.get_variant().unwrap();
+/// ```
+///
+/// This trait has a generic parameter `Marker` for adding marker
+/// structs. This is used if implementing this trait for enums with
+/// more than one generic parameter in order to avoid definition clashes.
+pub trait GetVariant<T, Marker = ()> {
+    fn get_variant(self) -> Result<T, VariantAccessError<Self>>
+    where
+        Self: Sized;
+}
+
 /// This trait is for extracting a reference to the raw
 /// values in an enum
 ///
-/// get_variant returns a reference to the raw value of
+/// get_variant_ref returns a reference to the raw value of
 /// the active field if it has the same type as the specified type.
-/// Otherwise, an Err should be returned. It is intended to use
-/// this function in conjunction with [`has_variant`] / [`contains_variant`]
-/// to know that safe unwrapping can occur.
+/// Otherwise, an Err should be returned.
 ///
 /// get_variant_mut is similar except it is for returning a mutable
 /// reference to the raw value of the active field.
@@ -96,24 +378,126 @@ pub trait ContainsVariant {
 ///     F1(i64),
 ///     F2(bool)
 /// }
-/// let instance = Enum::f1(42);
+/// let instance = Enum::F1(42);
 ///
-/// let inner: &i64 = instance.get_variant().unwrap(); // assigns &42 to inner_value
-/// // let inner: &bool = instance.get_variant().unwrap() // panics because of unhandled Err.
-/// // let inner: &i32 = instance.get_variant().unwrap() // will not compile as GetVariant<i32> is not implemented for Enum.
+/// let inner: &i64 = instance.get_variant_ref().unwrap(); // assigns &42 to inner_value
+/// // let inner: &bool = instance.get_variant_ref().unwrap() // panics because of unhandled Err.
+/// // let inner: &i32 = instance.get_variant_ref().unwrap() // will not compile as GetVariantRef<i32> is not implemented for Enum.
 /// ```
 /// Works similarly for get_variant_mut if instance is mutable;
 /// returns mutable references instead.
 ///
+/// This trait is only implemented for types that are stored as a
+/// single field of the enum. For variants with more than one field,
+/// see [`GetVariant`].
+///
 /// This trait has a generic parameter `Marker` for adding marker
 /// structs. This is used if implementing this trait for enums with
 /// more than one generic parameter in order to avoid definition clashes.
-pub trait GetVariant<T, Marker = ()> {
-    fn get_variant(self) -> Result<T, VariantAccessError>;
+pub trait GetVariantRef<T, Marker = ()>: GetVariant<T, Marker> {
     fn get_variant_ref(&self) -> Result<&T, VariantAccessError>;
     fn get_variant_mut(&mut self) -> Result<&mut T, VariantAccessError>;
 }
 
+/// This trait names the marker struct with which the traits in this
+/// crate are implemented for the type `T`, so that it need not be
+/// given when calling the inherent methods `get`, `get_mut`,
+/// `try_get`, `get_copied`, `get_cloned` and `get_or` of the enum.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+/// let mut instance = Enum::F1(42);
+///
+/// assert_eq!(instance.get::<i64>().unwrap(), &42);
+/// assert_eq!(instance.try_get::<bool>(), None);
+/// *instance.get_mut::<i64>().unwrap() += 1;
+/// assert_eq!(instance.get_copied::<i64>().unwrap() + 1, 44);
+/// assert_eq!(instance.get_or::<bool>(true), true);
+/// ```
+///
+/// This trait is only derived for enums whose field types do not
+/// depend on their generic type parameters, as these could be the
+/// same type as another field otherwise. The inherent methods of such
+/// enums take the marker as a second generic parameter instead, which
+/// can be inferred by writing `_`, e.g. `instance.get::<i64, _>()`.
+pub trait VariantMarker<T> {
+    type Marker;
+}
+
+/// This trait marks that the enum has a field of type `T`, so that
+/// generic code can require it in its bounds. Unlike `has_variant`,
+/// this is checked at compile time.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// fn is_number<E: HasVariant<i64>>(instance: &E) -> bool {
+///     instance.is_variant()
+/// }
+/// let instance = Enum::F1(42);
+/// assert!(is_number(&instance));
+/// assert!(instance.contains::<i64>());
+/// // instance.contains::<i32>(); will not compile as Enum has no field of type i32
+/// ```
+///
+/// It is derived along with IsVariant, for the same enums as
+/// [`VariantMarker`].
+pub trait HasVariant<T>: VariantMarker<T> + IsVariant<T, <Self as VariantMarker<T>>::Marker> {}
+
+/// This trait combines the traits for getting, setting and creating
+/// the field of type `T`, and is implemented for every enum that
+/// implements them along with [`HasVariant`].
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// fn reset<E: VariantAccess<i64>>(instance: &mut E) {
+///     instance.set_variant(0);
+/// }
+/// let mut instance = Enum::F2(true);
+/// reset(&mut instance);
+/// assert!(instance.contains::<i64>());
+/// ```
+pub trait VariantAccess<T>:
+    HasVariant<T>
+    + GetVariant<T, <Self as VariantMarker<T>>::Marker>
+    + SetVariant<T, <Self as VariantMarker<T>>::Marker>
+    + CreateVariantFrom<T, <Self as VariantMarker<T>>::Marker>
+{
+}
+
+impl<E, T> VariantAccess<T> for E where
+    E: HasVariant<T>
+        + GetVariant<T, <E as VariantMarker<T>>::Marker>
+        + SetVariant<T, <E as VariantMarker<T>>::Marker>
+        + CreateVariantFrom<T, <E as VariantMarker<T>>::Marker>
+{
+}
+
 /// This trait is for setting an inner value with the correct
 /// associated type to the given value
 ///
@@ -154,18 +538,44 @@ pub trait GetVariant<T, Marker = ()> {
 /// }
 ///
 /// let mut instance = Enum::F1(42);
-/// instance.set_variant(1); // Is instance equal to Enum::F1(1) or Enum::F2(1) ???
+/// // instance.set_variant(1); // Is instance equal to Enum::F1(1) or Enum::F2(1) ???
 ///
 /// // Do this instead
 /// instance.set_variant(1_i32); // instance equals Enum::F1(1)
 /// instance.set_variant(1_i64); // instance equal Enum::F2(1)
 /// ```
 ///
+/// replace_variant is similar except that it returns the previous
+/// value of the enum rather than dropping it.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, PartialEq, Debug)]
+/// enum Enum {
+///     F1(String),
+///     F2(bool)
+/// }
+///
+/// let mut instance = Enum::F1(String::from("42"));
+/// let previous = instance.replace_variant(false); // instance now is equal to Enum::F2(false)
+/// assert_eq!(previous, Enum::F1(String::from("42")));
+/// ```
+///
 /// This trait has a generic paramer `Marker` for adding marker
 /// structs. This is used if implementing this trait for enums with more
 /// than one generic parameter in order to avoid definition clashes.
 pub trait SetVariant<T, Marker = ()> {
     fn set_variant(&mut self, value: T);
+
+    fn replace_variant(&mut self, value: T) -> Self
+    where
+        Self: CreateVariantFrom<T, Marker> + Sized,
+    {
+        std::mem::replace(self, Self::create_variant_from(value))
+    }
 }
 
 /// This trait allows one to create a new instance of an enum
@@ -177,13 +587,14 @@ pub trait SetVariant<T, Marker = ()> {
 /// use variant_access_traits::*;
 /// use variant_access_derive::*;
 ///
+/// #[derive(VariantAccess)]
 /// enum Enum {
 ///     F1(i64),
 ///     F2(bool)
 /// }
 ///
-/// let instance = Enum::create_from(false); // instance is now equal to Enum::F2(false)
-/// // let instance = Enum::create_from("") // will not compile as Enum has no field of type &str
+/// let instance = Enum::create_variant_from(false); // instance is now equal to Enum::F2(false)
+/// // let instance = Enum::create_variant_from("") // will not compile as Enum has no field of type &str
 ///```
 /// Similar to the `SetVariant`, the `as` keyword should be used
 /// in the case of ambiguous typing.
@@ -203,7 +614,7 @@ pub trait CreateVariantFrom<T, Marker = ()> {
 /// use variant_access_traits::*;
 /// use variant_access_derive::*;
 ///
-/// #[(VariantAccessDerive)]
+/// #[derive(VariantAccess)]
 /// enum HorribleComputerGeneratedEnumName {
 ///     AwfulComputerGeneratedField1(f64),
 ///     AwfulComputerGeneratedField2(bool)
@@ -222,3 +633,824 @@ pub trait CreateVariantFrom<T, Marker = ()> {
 pub fn create_variant_from<T: CreateVariantFrom<U, Marker>, Marker, U>(value: U) -> T {
     T::create_variant_from(value)
 }
+
+/// This trait extends every value with a method turning it into an
+/// enum one of whose fields has its type, by way of
+/// [`CreateVariantFrom`]. Unlike `Into`, the enum can be named with
+/// turbofish syntax, so that it fits inside expressions.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, PartialEq, Debug)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// assert!(false.into_variant::<Enum>() == Enum::F2(false));
+/// // 42_i32.into_variant::<Enum>(); // will not compile as Enum has no field of type i32
+/// ```
+///
+/// The marker is found through [`VariantMarker`], so this is only
+/// available for enums for which it is derived. For other enums, use
+/// [`create_variant_from`] instead.
+pub trait IntoVariant: Sized {
+    fn into_variant<E>(self) -> E
+    where
+        E: VariantMarker<Self> + CreateVariantFrom<Self, <E as VariantMarker<Self>>::Marker>,
+    {
+        E::create_variant_from(self)
+    }
+}
+
+impl<T> IntoVariant for T {}
+
+/// This trait is for accessing the alternatives of an enum by
+/// their index, much like `std::get<I>` in C++.
+///
+/// Type is the type of the alternative at index I, where the
+/// alternatives are counted in the order they are declared, as in
+/// [`VariantTypes`]. set_at and create_at work like [`SetVariant`]
+/// and [`CreateVariantFrom`] for this type. As the index picks the
+/// alternative, the type of the value need not be annotated.
+///
+/// The derive macro also implements inherent methods `get_at`,
+/// `get_at_mut`, `set_at` and `create_at` taking the index as a
+/// const generic argument.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, Debug, PartialEq)]
+/// enum Enum {
+///     F1(i32),
+///     F2(i64)
+/// }
+///
+/// let mut instance = Enum::create_at::<0>(42); // instance is now equal to Enum::F1(42)
+/// instance.set_at::<1>(1); // instance now equals Enum::F2(1)
+/// let inner: &i64 = instance.get_at::<1>().unwrap(); // assigns &1 to inner
+/// // let inner = instance.get_at::<0>().unwrap(); // panics because of unhandled Err.
+/// // let inner = instance.get_at::<2>().unwrap(); // will not compile as Enum has only two alternatives
+/// let inner = VariantAtRef::<1>::get_at(&instance).unwrap(); // the same, using the trait directly
+/// ```
+pub trait VariantAt<const I: usize> {
+    type Type;
+    fn set_at(&mut self, value: Self::Type);
+    fn create_at(value: Self::Type) -> Self;
+}
+
+/// This trait is for borrowing the alternative of an enum at
+/// a given index, see [`VariantAt`].
+///
+/// get_at returns a reference to the raw value of the active field
+/// if it is the alternative at index I. Otherwise, an Err should be
+/// returned. get_at_mut is similar except it returns a mutable
+/// reference.
+///
+/// Like [`GetVariantRef`], this trait is only implemented for types
+/// that are stored as a single field of the enum.
+pub trait VariantAtRef<const I: usize>: VariantAt<I> {
+    fn get_at(&self) -> Result<&Self::Type, VariantAccessError>;
+    fn get_at_mut(&mut self) -> Result<&mut Self::Type, VariantAccessError>;
+}
+
+/// This trait is for visitors of the active field of an enum, much
+/// like the callables passed to `std::visit` in C++. It gives the
+/// type returned by the visitor, which is shared by each
+/// [`VisitType`] implementation.
+pub trait Visitor {
+    type Output;
+}
+
+/// This trait is for visiting fields of the specified type, see
+/// [`Visit`]. A single generic implementation can handle every
+/// alternative of an enum.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use std::fmt::Display;
+///
+/// struct Printer;
+///
+/// impl Visitor for Printer {
+///     type Output = String;
+/// }
+///
+/// impl<T: Display> VisitType<T> for Printer {
+///     fn visit_type(self, value: T) -> String {
+///         value.to_string()
+///     }
+/// }
+/// ```
+pub trait VisitType<T>: Visitor {
+    fn visit_type(self, value: T) -> Self::Output;
+}
+
+/// This trait is for running type-specific logic on the active
+/// field of an enum, without matching on the names of its variants.
+///
+/// visit passes a reference to the active field to the visitor,
+/// which must implement [`VisitType`] for references to the type of
+/// each field. [`VisitMut`] and [`IntoVisit`] are similar, passing
+/// mutable references and values respectively. These are separate
+/// traits, so that a visitor need only handle the kind of access
+/// that is used.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+/// use std::fmt::Display;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// struct Printer;
+///
+/// impl Visitor for Printer {
+///     type Output = String;
+/// }
+///
+/// impl<T: Display> VisitType<T> for Printer {
+///     fn visit_type(self, value: T) -> String {
+///         value.to_string()
+///     }
+/// }
+///
+/// let instance = Enum::F1(42);
+/// let result = instance.visit(Printer); // assigns "42" to result
+/// let result = instance.into_visit(Printer); // assigns "42" to result
+/// ```
+///
+/// Like [`GetVariant`], variants with more than one field are
+/// visited as the tuple of their fields, or of references to them.
+/// Variants with named fields are visited as their generated
+/// structs. As a visitor must handle every variant, these traits are
+/// not derived for enums with skipped variants.
+pub trait Visit<V: Visitor> {
+    fn visit(&self, visitor: V) -> V::Output;
+}
+
+/// This trait is for visiting a mutable reference to the active
+/// field of an enum, see [`Visit`].
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, Debug, PartialEq)]
+/// enum Enum {
+///     F1(i64),
+///     F2(u8)
+/// }
+///
+/// struct Reset;
+///
+/// impl Visitor for Reset {
+///     type Output = ();
+/// }
+///
+/// impl<T: Default> VisitType<&mut T> for Reset {
+///     fn visit_type(self, value: &mut T) {
+///         *value = T::default();
+///     }
+/// }
+///
+/// let mut instance = Enum::F1(42);
+/// instance.visit_mut(Reset); // instance now is equal to Enum::F1(0)
+/// ```
+pub trait VisitMut<V: Visitor> {
+    fn visit_mut(&mut self, visitor: V) -> V::Output;
+}
+
+/// This trait is for visiting the active field of an enum by
+/// value, see [`Visit`].
+pub trait IntoVisit<V: Visitor> {
+    fn into_visit(self, visitor: V) -> V::Output;
+}
+
+/// This trait is for visiting the active fields of two enums at
+/// once, much like `std::visit` with two variants in C++, see
+/// [`visit2`]. The visitor implements it for each combination of
+/// types it handles.
+///
+/// Combinations that are not handled are compile errors. A fallback
+/// can be given by a generic implementation, as long as it does not
+/// overlap with the others, e.g. by bounding it on a trait only
+/// implemented by the remaining types.
+pub trait VisitPair<A, B>: Visitor {
+    fn visit_pair(self, first: A, second: B) -> Self::Output;
+}
+
+/// This trait is for visiting the active fields of any number of
+/// enums at once, see [`visit!`]. The visited values are passed as
+/// a tuple. Visitors implementing [`VisitPair`] implement this for
+/// pairs.
+pub trait VisitTuple<Args>: Visitor {
+    fn visit_tuple(self, args: Args) -> Self::Output;
+}
+
+impl<V: VisitPair<A, B>, A, B> VisitTuple<(A, B)> for V {
+    fn visit_tuple(self, (first, second): (A, B)) -> Self::Output {
+        self.visit_pair(first, second)
+    }
+}
+
+/// Visits references to the active fields of two enums, passing them
+/// to the visitor's [`VisitPair`] implementation for their types.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Operand {
+///     Int(i64),
+///     Float(f64)
+/// }
+///
+/// struct Add;
+///
+/// impl Visitor for Add {
+///     type Output = f64;
+/// }
+///
+/// impl VisitPair<&i64, &i64> for Add {
+///     fn visit_pair(self, first: &i64, second: &i64) -> f64 {
+///         (first + second) as f64
+///     }
+/// }
+///
+/// impl VisitPair<&i64, &f64> for Add {
+///     fn visit_pair(self, first: &i64, second: &f64) -> f64 {
+///         *first as f64 + second
+///     }
+/// }
+///
+/// impl VisitPair<&f64, &i64> for Add {
+///     fn visit_pair(self, first: &f64, second: &i64) -> f64 {
+///         first + *second as f64
+///     }
+/// }
+///
+/// impl VisitPair<&f64, &f64> for Add {
+///     fn visit_pair(self, first: &f64, second: &f64) -> f64 {
+///         first + second
+///     }
+/// }
+///
+/// let result = visit2(&Operand::Int(1), &Operand::Float(0.5), Add); // assigns 1.5 to result
+/// ```
+pub fn visit2<'a, A, B, V>(first: &A, second: &'a B, visitor: V) -> V::Output
+where
+    V: Visitor,
+    A: Visit<Dispatch<(), (&'a B, ()), V>>,
+{
+    first.visit(Dispatch::new((), (second, ()), visitor))
+}
+
+/// Visits references to the active fields of any number of enums,
+/// passing them as a tuple to the visitor's [`VisitTuple`]
+/// implementation for their types.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+/// use std::fmt::Display;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// struct Join;
+///
+/// impl Visitor for Join {
+///     type Output = String;
+/// }
+///
+/// impl<A: Display, B: Display, C: Display> VisitTuple<(A, B, C)> for Join {
+///     fn visit_tuple(self, (a, b, c): (A, B, C)) -> String {
+///         format!("{} {} {}", a, b, c)
+///     }
+/// }
+///
+/// let result = visit!(Join; &Enum::F1(1), &Enum::F2(true), &Enum::F1(2)); // assigns "1 true 2" to result
+/// ```
+#[macro_export]
+macro_rules! visit {
+    ($visitor:expr; $first:expr $(, $rest:expr)* $(,)?) => {
+        $crate::Visit::visit(
+            $first,
+            $crate::Dispatch::new((), $crate::visit!(@rest $($rest),*), $visitor),
+        )
+    };
+    (@rest) => { () };
+    (@rest $next:expr $(, $rest:expr)*) => { ($next, $crate::visit!(@rest $($rest),*)) };
+}
+
+/// The visitor used by [`visit2`] and [`visit!`] to visit each enum
+/// in turn. It holds the tuple of the values visited so far, the
+/// enums remaining to be visited as a list of the form
+/// `(&E1, (&E2, ()))`, and the visitor given by the user.
+#[doc(hidden)]
+pub struct Dispatch<Done, Rest, V> {
+    done: Done,
+    rest: Rest,
+    visitor: V,
+}
+
+impl<Done, Rest, V> Dispatch<Done, Rest, V> {
+    pub fn new(done: Done, rest: Rest, visitor: V) -> Self {
+        Dispatch { done, rest, visitor }
+    }
+}
+
+impl<Done, Rest, V: Visitor> Visitor for Dispatch<Done, Rest, V> {
+    type Output = V::Output;
+}
+
+impl<Done, E, Rest, V, X> VisitType<X> for Dispatch<Done, (&E, Rest), V>
+where
+    Done: Push<X>,
+    V: Visitor,
+    E: Visit<Dispatch<Done::Output, Rest, V>>,
+{
+    fn visit_type(self, value: X) -> V::Output {
+        let (next, rest) = self.rest;
+        next.visit(Dispatch::new(self.done.push(value), rest, self.visitor))
+    }
+}
+
+impl<Done, V, X> VisitType<X> for Dispatch<Done, (), V>
+where
+    Done: Push<X>,
+    V: VisitTuple<Done::Output>,
+{
+    fn visit_type(self, value: X) -> V::Output {
+        self.visitor.visit_tuple(self.done.push(value))
+    }
+}
+
+/// Appends a value to a tuple, as needed by [`Dispatch`]
+#[doc(hidden)]
+pub trait Push<X> {
+    type Output;
+    fn push(self, value: X) -> Self::Output;
+}
+
+macro_rules! impl_push {
+    ($($name:ident),*) => {
+        impl<$($name,)* X> Push<X> for ($($name,)*) {
+            type Output = ($($name,)* X,);
+
+            #[allow(non_snake_case)]
+            fn push(self, value: X) -> Self::Output {
+                let ($($name,)*) = self;
+                ($($name,)* value,)
+            }
+        }
+    };
+}
+
+impl_push!();
+impl_push!(A);
+impl_push!(A, B);
+impl_push!(A, B, C);
+impl_push!(A, B, C, D);
+impl_push!(A, B, C, D, E);
+impl_push!(A, B, C, D, E, F);
+impl_push!(A, B, C, D, E, F, G);
+
+/// This trait lists the alternatives of an enum at the type level,
+/// in the order they are declared. List is a nested tuple of the
+/// form `(T1, (T2, (T3, ())))`.
+///
+/// It is used by `match_variant!` to check that every alternative is
+/// handled. As skipped variants cannot be handled, this trait is not
+/// derived for enums with skipped variants.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+///enum Enum {
+///    F1(i64),
+///    F2(bool)
+///}
+/// let list: <Enum as Alternatives>::List = (1_i64, (true, ()));
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "the alternatives of `{Self}` are not listed",
+    note = "enums with skipped variants do not list their alternatives; add a `_` arm to `match_variant!`"
+)]
+pub trait Alternatives {
+    type List;
+}
+
+/// Checks that the alternatives of the enum are exactly the types in
+/// the list `Arms`, of the same form as [`Alternatives::List`] but in
+/// any order. Used by `match_variant!`.
+#[doc(hidden)]
+pub fn check_exhaustive<E, Arms, Indices>(_: &E)
+where
+    E: Alternatives,
+    E::List: PluckAll<Arms, Indices>,
+{
+}
+
+/// The index of the first element of a type-level list
+#[doc(hidden)]
+pub struct Here;
+
+/// The index of an element after the first of a type-level list
+#[doc(hidden)]
+pub struct There<I>(PhantomData<I>);
+
+/// Removes the type `T` at index `I` from a type-level list
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(message = "`{T}` is not an alternative of the enum, or is handled twice")]
+pub trait Pluck<T, I> {
+    type Rest;
+}
+
+impl<T, Tail> Pluck<T, Here> for (T, Tail) {
+    type Rest = Tail;
+}
+
+impl<T, Head, Tail: Pluck<T, I>, I> Pluck<T, There<I>> for (Head, Tail) {
+    type Rest = (Head, Tail::Rest);
+}
+
+/// Removes each of the types in `Arms` from a type-level list,
+/// leaving it empty
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`match_variant!` does not handle every alternative of the enum",
+    label = "the types in `{Self}` are not handled",
+    note = "add an arm for each of these types, or a `_` arm"
+)]
+pub trait PluckAll<Arms, Indices> {}
+
+impl PluckAll<(), ()> for () {}
+
+impl<List, T, Arms, I, Indices> PluckAll<(T, Arms), (I, Indices)> for List
+where
+    List: Pluck<T, I>,
+    List::Rest: PluckAll<Arms, Indices>,
+{
+}
+
+/// Conversions between two enums whose alternatives overlap, built on
+/// [`GetVariant`] and [`CreateVariantFrom`]. It is implemented for
+/// every type, and needs the enums to derive [`Alternatives`].
+///
+/// Named variants are not shared between enums, as each enum has its
+/// own marker structs.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess, PartialEq, Debug)]
+/// enum Scalar {
+///     Int(i64),
+///     Float(f64)
+/// }
+///
+/// #[derive(VariantAccess, PartialEq, Debug)]
+/// enum Value {
+///     Int(i64),
+///     Float(f64),
+///     Text(String)
+/// }
+///
+/// let value = Value::widen_from(Scalar::Int(1));
+/// assert_eq!(value, Value::Int(1));
+/// assert_eq!(Scalar::narrow_from(value), Ok(Scalar::Int(1)));
+/// assert_eq!(Scalar::narrow_from(Value::Text("a".into())), Err(Value::Text("a".into())));
+/// ```
+pub trait ConvertVariant: Sized {
+    /// Converts from an enum whose alternatives are all alternatives of
+    /// `Self`. This does not compile if `Source` has an alternative
+    /// that `Self` does not.
+    fn widen_from<Source, Markers>(source: Source) -> Self
+    where
+        Source: Alternatives,
+        Source::List: ConvertAlternatives<Source, Self, Markers>,
+    {
+        match <Source::List>::convert(source) {
+            Ok(value) => value,
+            Err(_) => unreachable!("Alternatives::List does not cover every alternative of the enum"),
+        }
+    }
+
+    /// Converts from an enum that has all the alternatives of `Self`
+    /// and possibly more. If the active field of `source` is not an
+    /// alternative of `Self`, `source` is returned.
+    fn narrow_from<Source, Markers>(source: Source) -> Result<Self, Source>
+    where
+        Self: Alternatives,
+        <Self as Alternatives>::List: ConvertAlternatives<Source, Self, Markers>,
+    {
+        <<Self as Alternatives>::List>::convert(source)
+    }
+}
+
+impl<T> ConvertVariant for T {}
+
+/// Tries each of the types in a type-level list in turn, taking the
+/// active field of `Source` if it is of that type and creating a
+/// `Target` from it. Used by [`ConvertVariant`].
+#[doc(hidden)]
+pub trait ConvertAlternatives<Source, Target, Markers> {
+    fn convert(source: Source) -> Result<Target, Source>;
+}
+
+impl<Source, Target> ConvertAlternatives<Source, Target, ()> for () {
+    fn convert(source: Source) -> Result<Target, Source> {
+        Err(source)
+    }
+}
+
+impl<Source, Target, T, Tail, GetMarker, CreateMarker, Markers>
+    ConvertAlternatives<Source, Target, ((GetMarker, CreateMarker), Markers)> for (T, Tail)
+where
+    Source: GetVariant<T, GetMarker>,
+    Target: CreateVariantFrom<T, CreateMarker>,
+    Tail: ConvertAlternatives<Source, Target, Markers>,
+{
+    fn convert(source: Source) -> Result<Target, Source> {
+        match source.get_variant() {
+            Ok(value) => Ok(Target::create_variant_from(value)),
+            Err(error) => Tail::convert(error.into_inner()),
+        }
+    }
+}
+
+/// This trait is for generic code run once for each type in a list,
+/// such as the alternatives of an enum given by [`Alternatives`].
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool)
+/// }
+///
+/// struct Names(Vec<&'static str>);
+///
+/// impl TypeVisitor for Names {
+///     fn visit<T>(&mut self) {
+///         self.0.push(std::any::type_name::<T>());
+///     }
+/// }
+///
+/// let mut names = Names(vec![]);
+/// for_each_type::<Enum, _>(&mut names);
+/// assert_eq!(names.0, vec!["i64", "bool"]);
+/// ```
+pub trait TypeVisitor {
+    fn visit<T>(&mut self);
+}
+
+/// A type-level list of the form `(T1, (T2, (T3, ())))`, as given by
+/// [`Alternatives::List`].
+pub trait TypeList {
+    /// The number of types in the list
+    const LEN: usize;
+
+    /// Calls the visitor once for each type in the list, in order
+    fn for_each<V: TypeVisitor>(visitor: &mut V);
+}
+
+impl TypeList for () {
+    const LEN: usize = 0;
+
+    fn for_each<V: TypeVisitor>(_: &mut V) {}
+}
+
+impl<T, Tail: TypeList> TypeList for (T, Tail) {
+    const LEN: usize = Tail::LEN + 1;
+
+    fn for_each<V: TypeVisitor>(visitor: &mut V) {
+        visitor.visit::<T>();
+        Tail::for_each(visitor);
+    }
+}
+
+/// Calls the visitor once for each alternative of the enum `E`, in
+/// the order they are declared. See [`TypeVisitor`].
+pub fn for_each_type<E, V>(visitor: &mut V)
+where
+    E: Alternatives,
+    E::List: TypeList,
+    V: TypeVisitor,
+{
+    E::List::for_each(visitor)
+}
+
+/// This trait is for checking if the active field of the enum `E` is
+/// of any of the types in a tuple such as `(i64, bool)`. Every type
+/// must be a field of the enum, as with [`HasVariant`]. It is used by
+/// the inherent method `contains_any` of the enum.
+///
+/// # Example
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// #[derive(VariantAccess)]
+/// enum Enum {
+///     F1(i64),
+///     F2(bool),
+///     F3(String)
+/// }
+///
+/// let instance = Enum::F2(true);
+/// assert!(instance.contains_any::<(i64, bool)>());
+/// assert!(!instance.contains_any::<(String,)>());
+/// // instance.contains_any::<(i64, i32)>(); will not compile as Enum has no field of type i32
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a tuple of field types of `{E}`",
+    note = "every type in the tuple must be a field type of the enum"
+)]
+pub trait VariantSet<E> {
+    fn any_active(value: &E) -> bool;
+}
+
+macro_rules! impl_variant_set {
+    ($($name:ident),*) => {
+        impl<Enum, $($name,)*> VariantSet<Enum> for ($($name,)*)
+        where
+            $(Enum: HasVariant<$name>,)*
+        {
+            #[allow(unused_variables)]
+            fn any_active(value: &Enum) -> bool {
+                false $(|| IsVariant::<$name, <Enum as VariantMarker<$name>>::Marker>::is_variant(value))*
+            }
+        }
+    };
+}
+
+impl_variant_set!();
+impl_variant_set!(A);
+impl_variant_set!(A, B);
+impl_variant_set!(A, B, C);
+impl_variant_set!(A, B, C, D);
+impl_variant_set!(A, B, C, D, E);
+impl_variant_set!(A, B, C, D, E, F);
+impl_variant_set!(A, B, C, D, E, F, G);
+
+/// A value of type `T` tagged with the type `Tag`. This gives distinct
+/// types to variants whose fields have the same type, so that they
+/// can still be told apart by the traits in this crate.
+///
+/// The derive macro uses this for variants marked with
+/// `#[variant_access(tag = Tag)]`. Without a type, i.e.
+/// `#[variant_access(tag)]`, the marker struct of the variant is used
+/// as the tag.
+///
+/// # Example:
+/// ```
+/// use variant_access_traits::*;
+/// use variant_access_derive::*;
+///
+/// pub struct Meters;
+///
+/// #[derive(VariantAccess)]
+/// enum Length {
+///     #[variant_access(tag = Meters)]
+///     Meters(f64),
+///     #[variant_access(tag)]
+///     Feet(f64),
+///     Unknown(bool)
+/// }
+///
+/// let mut instance = Length::create_variant_from(Tagged::<Meters, _>::new(1.0));
+/// let inner: &Tagged<Meters, f64> = instance.get_variant_ref().unwrap(); // assigns &1.0 to *inner
+/// instance.set_variant(Tagged::<variant_access_Length::Feet, _>::new(3.0)); // instance now is equal to Length::Feet(3.0)
+/// instance.set_variant(false); // fields of other variants are accessed as usual
+/// ```
+///
+/// Tagged values dereference to the inner value. As `Tagged` is a
+/// transparent wrapper, references to a value can be converted to
+/// references to the tagged value, which is how the fields of tagged
+/// variants are borrowed.
+#[repr(transparent)]
+pub struct Tagged<Tag, T> {
+    pub value: T,
+    tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag, T> Tagged<Tag, T> {
+    /// Tags the value
+    pub fn new(value: T) -> Self {
+        Tagged {
+            value,
+            tag: PhantomData,
+        }
+    }
+
+    /// Removes the tag from the value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Tags a borrowed value
+    pub fn from_ref(value: &T) -> &Self {
+        // Tagged is a transparent wrapper around T
+        unsafe { &*(value as *const T as *const Self) }
+    }
+
+    /// Tags a mutably borrowed value
+    pub fn from_mut(value: &mut T) -> &mut Self {
+        // Tagged is a transparent wrapper around T
+        unsafe { &mut *(value as *mut T as *mut Self) }
+    }
+}
+
+impl<Tag, T> Deref for Tagged<Tag, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<Tag, T> DerefMut for Tagged<Tag, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// The following are implemented by hand, as deriving them
+// would require the tag to implement them as well.
+
+impl<Tag, T: fmt::Debug> fmt::Debug for Tagged<Tag, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Tagged").field(&self.value).finish()
+    }
+}
+
+impl<Tag, T: Clone> Clone for Tagged<Tag, T> {
+    fn clone(&self) -> Self {
+        Tagged::new(self.value.clone())
+    }
+}
+
+impl<Tag, T: Copy> Copy for Tagged<Tag, T> {}
+
+impl<Tag, T: Default> Default for Tagged<Tag, T> {
+    fn default() -> Self {
+        Tagged::new(T::default())
+    }
+}
+
+impl<Tag, T: PartialEq> PartialEq for Tagged<Tag, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<Tag, T: Eq> Eq for Tagged<Tag, T> {}
+
+impl<Tag, T: PartialOrd> PartialOrd for Tagged<Tag, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<Tag, T: Ord> Ord for Tagged<Tag, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<Tag, T: Hash> Hash for Tagged<Tag, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}